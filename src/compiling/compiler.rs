@@ -1,300 +1,571 @@
 use super::opcode::OpCode;
+use crate::diagnostic::Diagnostic;
+use crate::error::{ErrorKind, KlangError};
 use crate::interpreter::expr::Expr;
 use crate::interpreter::stmt::Stmt;
-use crate::scanner::{TokenType, Value};
+use crate::scanner::{fill_holes, Span, Token, TokenType, Value};
 
 #[derive(Debug, Clone)]
 pub struct Chunk {
     pub code: Vec<OpCode>,
-    pub lines: Vec<usize>,
+    pub spans: Vec<Span>, // the source span of every opcode
 }
 
 impl Chunk {
-    pub fn new(stuff: (Vec<OpCode>, Vec<usize>)) -> Chunk {
+    pub fn new(stuff: (Vec<OpCode>, Vec<Span>)) -> Chunk {
         Chunk {
             code: stuff.0,
-            lines: stuff.1,
+            spans: stuff.1,
         }
     }
 }
 
-pub fn compile(stmts: Vec<Stmt>) -> (Vec<OpCode>, Vec<usize>) {
+pub fn compile(stmts: Vec<Stmt>) -> Result<(Vec<OpCode>, Vec<Span>), KlangError> {
     let mut code: Vec<OpCode> = Vec::new();
-    let mut lines: Vec<usize> = Vec::new();
+    let mut spans: Vec<Span> = Vec::new();
     for stmt in stmts {
         match stmt {
-            Stmt::Print(x, line) => {
-                match x {
-                    Value::String { string, printables } => {
-                        for i in printables {
-                            dump(&mut code, &mut lines, compile_expr(i))
-                        }
-                        code.push(OpCode::Constant(Value::String {
-                            string,
-                            printables: Vec::new(),
-                        }));
-                        lines.push(line);
-                    }
-                    _ => {
-                        code.push(OpCode::Constant(x));
-                        lines.push(line);
-                    }
-                };
+            Stmt::Print(x, span) => {
+                dump(&mut code, &mut spans, compile_expr(Expr::Literal(x, span))?);
                 code.push(OpCode::Print);
-                lines.push(line);
+                spans.push(span);
             }
             Stmt::Block(stmts, (start, end)) => {
                 code.push(OpCode::Scope);
-                lines.push(start);
-                dump(&mut code, &mut lines, compile(stmts));
+                spans.push(start);
+                dump(&mut code, &mut spans, compile(stmts)?);
                 code.pop();
-                lines.pop();
+                spans.pop();
                 code.push(OpCode::EndScope);
-                lines.push(end);
+                spans.push(end);
             }
-            Stmt::Expression(expr) => dump(&mut code, &mut lines, compile_expr(expr)),
+            Stmt::Expression(expr) => dump(&mut code, &mut spans, compile_expr(expr)?),
             Stmt::If {
                 condition,
                 block,
                 elseblock,
-                lines: line,
+                spans: span,
             } => {
-                dump(&mut code, &mut lines, compile_expr(condition));
+                dump(&mut code, &mut spans, compile_expr(condition)?);
                 code.push(OpCode::LogicalNot); //jump if false
-                lines.push(line.0);
+                spans.push(span.0);
                 let b_vec: Vec<Stmt> = vec![*block];
-                let blok = compile(b_vec);
-                code.push(OpCode::JumpIf(blok.0.len() as i32 - 1, elseblock.is_none()));
-                lines.push(line.0);
-                dump(&mut code, &mut lines, blok);
-                code.pop();
-                lines.pop();
-                if elseblock.is_some() {
-                    code.push(OpCode::LogicalNot); //jump if true
-                    lines.push(line.1.unwrap());
-                    let b_vec: Vec<Stmt> = vec![*elseblock.unwrap()];
-                    let blok = compile(b_vec);
-                    code.push(OpCode::JumpIf(blok.0.len() as i32 - 1, true));
-                    lines.push(line.1.unwrap());
-                    dump(&mut code, &mut lines, blok);
-                    code.pop();
-                    lines.pop();
+                let mut blok = compile(b_vec)?;
+                blok.0.pop();
+                blok.1.pop();
+                let block_len = blok.0.len() as i32;
+                match elseblock {
+                    Some(elseblock) => {
+                        //an else if compiles to another if right here, so every branch
+                        //ends with one jump past the whole chain
+                        let b_vec: Vec<Stmt> = vec![*elseblock];
+                        let mut elseblok = compile(b_vec)?;
+                        elseblok.0.pop();
+                        elseblok.1.pop();
+                        code.push(OpCode::JumpIf(block_len + 1, true));
+                        spans.push(span.0);
+                        dump(&mut code, &mut spans, blok);
+                        code.push(OpCode::Jump(elseblok.0.len() as i32));
+                        spans.push(span.1.unwrap());
+                        dump(&mut code, &mut spans, elseblok);
+                    }
+                    None => {
+                        code.push(OpCode::JumpIf(block_len, true));
+                        spans.push(span.0);
+                        dump(&mut code, &mut spans, blok);
+                    }
                 }
             }
-            Stmt::Var { name, value } => {
+            Stmt::Var {
+                name, value, span, ..
+            } => {
                 match value {
-                    Some(value) => dump(&mut code, &mut lines, compile_expr(value)),
+                    Some(value) => dump(&mut code, &mut spans, compile_expr(value)?),
                     None => {
                         code.push(OpCode::Constant(Value::None));
-                        lines.push(name.line)
+                        spans.push(span)
                     }
                 }
-                code.push(OpCode::Store(name.lexeme));
-                lines.push(name.line)
+                code.push(OpCode::Let(name.lexeme));
+                spans.push(span)
             }
             Stmt::While {
                 condition,
                 block,
-                line,
+                span,
+                label,
             } => {
-                let condition = compile_expr(condition);
+                let condition = compile_expr(condition)?;
                 let con_len = condition.0.len() as i32;
-                dump(&mut code, &mut lines, condition);
+                dump(&mut code, &mut spans, condition);
                 code.push(OpCode::LogicalNot); //jump if false
-                lines.push(line);
+                spans.push(span);
                 let b_vec: Vec<Stmt> = vec![*block];
-                let blok = compile(b_vec);
+                let mut blok = compile(b_vec)?;
                 let block_len = blok.0.len() as i32;
+                //the body starts with its own Scope, continue goes back to the condition
+                patch_loop(&mut blok.0, label, 0, -(con_len + 3), false);
                 code.push(OpCode::JumpIf(block_len, true));
-                lines.push(line);
-                dump(&mut code, &mut lines, blok);
+                spans.push(span);
+                dump(&mut code, &mut spans, blok);
                 code.pop();
-                lines.pop();
+                spans.pop();
                 code.push(OpCode::Jump(-(block_len + con_len + 2)));
-                lines.push(line);
+                spans.push(span);
             }
             Stmt::For {
                 identifier,
+                value,
                 iterable,
                 block,
-                line,
+                span,
+                label,
             } => {
-                dump(&mut code, &mut lines, compile_expr(iterable));
+                dump(&mut code, &mut spans, compile_expr(*iterable)?);
+                if value.is_some() {
+                    code.push(OpCode::Entries);
+                    spans.push(span);
+                }
+                code.push(OpCode::Iterate);
+                spans.push(span);
                 code.push(OpCode::For);
-                lines.push(line);
+                spans.push(span);
                 code.push(OpCode::Scope);
-                lines.push(line);
-                code.push(OpCode::Store(identifier.lexeme));
-                lines.push(line);
+                spans.push(span);
+                match value {
+                    Some(value) => code.push(OpCode::Unpack(identifier.lexeme, value.lexeme)),
+                    None => code.push(OpCode::Let(identifier.lexeme)),
+                }
+                spans.push(span);
                 let b_vec: Vec<Stmt> = vec![*block];
-                let mut blok = compile(b_vec);
+                let mut blok = compile(b_vec)?;
                 blok.0.remove(0);
                 blok.1.remove(0);
                 let block_len = blok.0.len() as i32;
+                //the scope For opened is already there, continue goes back to For
+                patch_loop(&mut blok.0, label, 1, -5, true);
                 code.push(OpCode::JumpIf(block_len, true));
-                lines.push(line);
-                dump(&mut code, &mut lines, blok);
+                spans.push(span);
+                dump(&mut code, &mut spans, blok);
                 code.pop();
-                lines.pop();
+                spans.pop();
                 code.push(OpCode::Jump(-(block_len + 4)));
-                lines.push(line);
+                spans.push(span);
+            }
+            Stmt::Fn {
+                name,
+                params,
+                body,
+                span,
+                ..
+            } => {
+                let lexeme = name.lexeme.clone();
+                dump(
+                    &mut code,
+                    &mut spans,
+                    function(Some(lexeme), params, *body, span)?,
+                );
+                code.push(OpCode::Let(name.lexeme));
+                spans.push(name.span);
+            }
+            Stmt::Struct {
+                name, fields, span, ..
+            } => {
+                let fields = fields.into_iter().map(|field| field.lexeme).collect();
+                code.push(OpCode::Struct(name.lexeme.clone(), fields));
+                spans.push(span);
+                code.push(OpCode::Let(name.lexeme));
+                spans.push(name.span);
             }
-            Stmt::Fn { name, params, body } => {
-                code.push(OpCode::Fn);
-                lines.push(name.line);
-                for i in params {
-                    code.push(OpCode::Store(i.lexeme));
-                    lines.push(name.line);
+            Stmt::Impl {
+                name,
+                methods,
+                span,
+            } => {
+                //the struct goes under its functions, Impl puts them into it
+                code.push(OpCode::Load(name.lexeme.clone()));
+                spans.push(name.span);
+                let mut names = Vec::new();
+                for method in methods {
+                    if let Stmt::Fn {
+                        name: method,
+                        params,
+                        body,
+                        span,
+                        ..
+                    } = method
+                    {
+                        let full = format!("{}::{}", name.lexeme, method.lexeme);
+                        dump(
+                            &mut code,
+                            &mut spans,
+                            function(Some(full), params, *body, span)?,
+                        );
+                        names.push(method.lexeme);
+                    }
                 }
-                let b_vec: Vec<Stmt> = vec![*body];
-                dump(&mut code, &mut lines, compile(b_vec));
-                code.pop();
-                lines.pop();
-                code.push(OpCode::Store(name.lexeme));
-                lines.push(name.line);
+                code.push(OpCode::Impl(names));
+                spans.push(span);
             }
-            Stmt::Return(expr, line) => match expr {
+            Stmt::Return(expr, span) => match expr {
                 Some(expr) => {
-                    dump(&mut code, &mut lines, compile_expr(expr));
+                    dump(&mut code, &mut spans, compile_expr(expr)?);
                     code.push(OpCode::Return(true));
-                    lines.push(line)
+                    spans.push(span)
                 }
                 None => {
                     code.push(OpCode::Return(false));
-                    lines.push(line)
+                    spans.push(span)
                 }
             },
+            Stmt::Break(label, span) => {
+                code.push(OpCode::Break(label.map(|label| label.lexeme)));
+                spans.push(span);
+            }
+            Stmt::Continue(label, span) => {
+                code.push(OpCode::Continue(label.map(|label| label.lexeme)));
+                spans.push(span);
+            }
         }
     }
     code.push(OpCode::Eof);
-    lines.push(0);
-    for (i, op) in code.clone().into_iter().enumerate() {
-        if matches!(op, OpCode::Call(_)) {
-            if let OpCode::Call(x) = &code[i] {
-                code[i] = OpCode::Call(x.clone());
+    spans.push(Span::default());
+    Ok((code, spans))
+}
+
+//Fn makes the function value and jumps over the body that comes right after it.
+//the body ends with a return so falling off the end gives back nada
+fn function(
+    name: Option<String>,
+    params: Vec<Token>,
+    body: Stmt,
+    span: Span,
+) -> Result<(Vec<OpCode>, Vec<Span>), KlangError> {
+    let mut body = compile(vec![body])?;
+    body.0.pop();
+    body.1.pop();
+    body.0.push(OpCode::Return(false));
+    body.1.push(span);
+    let params: Vec<String> = params.into_iter().map(|param| param.lexeme).collect();
+    //every variable the body (and the functions inside it) touches that isnt an argument,
+    //and if the body never makes it itself so it can only come from around the function
+    let declared: Vec<&String> = body
+        .0
+        .iter()
+        .flat_map(|op| match op {
+            OpCode::Let(x) => vec![x],
+            OpCode::Unpack(x, y) => vec![x, y],
+            _ => vec![],
+        })
+        .collect();
+    let mut free: Vec<(String, bool)> = Vec::new();
+    for op in &body.0 {
+        let names = match op {
+            OpCode::Load(x) | OpCode::Store(x) | OpCode::Let(x) => vec![x],
+            OpCode::Unpack(x, y) => vec![x, y],
+            _ => continue,
+        };
+        for x in names {
+            if !params.contains(x)
+                && name.as_ref() != Some(x)
+                && !free.iter().any(|(name, _)| name == x)
+            {
+                free.push((x.clone(), !declared.contains(&x)));
             }
         }
     }
-    (code, lines)
+    let mut code = vec![OpCode::Fn(name, params, free, body.0.len() as i32)];
+    let mut spans = vec![span];
+    dump(&mut code, &mut spans, body);
+    Ok((code, spans))
 }
 
-pub fn compile_expr(expr: Expr) -> (Vec<OpCode>, Vec<usize>) {
+pub fn compile_expr(expr: Expr) -> Result<(Vec<OpCode>, Vec<Span>), KlangError> {
     let mut code: Vec<OpCode> = Vec::new();
-    let mut lines: Vec<usize> = Vec::new();
+    let mut spans: Vec<Span> = Vec::new();
 
     match expr {
-        Expr::Assign { name, value } => {
-            dump(&mut code, &mut lines, compile_expr(*value));
+        Expr::Assign {
+            name,
+            value,
+            operator,
+            span,
+        } => {
+            if operator.is_some() {
+                code.push(OpCode::Load(name.lexeme.clone()));
+                spans.push(name.span);
+            }
+            dump(&mut code, &mut spans, compile_expr(*value)?);
+            if let Some(operator) = &operator {
+                code.push(bin(operator)?);
+                spans.push(span);
+            }
             code.push(OpCode::Store(name.lexeme));
-            lines.push(name.line)
+            spans.push(span)
         }
         Expr::Binary {
             left,
             operator,
             right,
+            span,
         } => {
-            dump(&mut code, &mut lines, compile_expr(*left));
-            dump(&mut code, &mut lines, compile_expr(*right));
-            code.push(bin(operator.tt));
-            lines.push(operator.line)
+            dump(&mut code, &mut spans, compile_expr(*left)?);
+            dump(&mut code, &mut spans, compile_expr(*right)?);
+            code.push(bin(&operator)?);
+            spans.push(span)
         }
         Expr::Call {
             callee,
             arguments,
             native,
+            span,
         } => {
-            let line;
             let len = arguments.len() as i32;
-            for arg_expr in arguments {
-                dump(&mut code, &mut lines, compile_expr(arg_expr));
-            }
             if native {
-                code.push(OpCode::NativeCall(
-                    match *callee {
-                        Expr::Variable(t) => {
-                            lines.push(t.line);
-                            line = t.line;
-                            t.lexeme
-                        }
-                        _ => unreachable!(),
-                    },
-                    len,
-                ));
+                for arg_expr in arguments {
+                    dump(&mut code, &mut spans, compile_expr(arg_expr)?);
+                }
+                let name = match *callee {
+                    Expr::Variable(t) => t.lexeme,
+                    callee => return Err(error(callee.span(), "can only call natives by name")),
+                };
+                code.push(OpCode::NativeCall(name, len));
+            } else if let Expr::Get { object, name, .. } = *callee {
+                //p.f() calls the method f of p, the vm works out what f is when it runs
+                dump(&mut code, &mut spans, compile_expr(*object)?);
+                for arg_expr in arguments {
+                    dump(&mut code, &mut spans, compile_expr(arg_expr)?);
+                }
+                code.push(OpCode::Invoke(name.lexeme, len));
             } else {
-                code.push(OpCode::Call(match *callee {
-                    Expr::Variable(t) => {
-                        lines.push(t.line);
-                        line = t.line;
-                        t.lexeme
-                    }
-                    _ => unreachable!(),
-                }));
+                //the function goes under its arguments
+                dump(&mut code, &mut spans, compile_expr(*callee)?);
+                for arg_expr in arguments {
+                    dump(&mut code, &mut spans, compile_expr(arg_expr)?);
+                }
+                code.push(OpCode::Call(len));
             }
-            lines.push(line);
+            spans.push(span);
         }
-        Expr::Grouping(expression) => dump(&mut code, &mut lines, compile_expr(*expression)),
-        Expr::Literal(x, line) => match x {
+        Expr::Grouping(expression, _) => dump(&mut code, &mut spans, compile_expr(*expression)?),
+        Expr::Literal(x, span) => match x {
+            Value::String { string, printables } if printables.is_empty() => {
+                code.push(OpCode::Constant(Value::String {
+                    string: fill_holes(&string, &[]),
+                    printables,
+                }));
+                spans.push(span);
+            }
             Value::String { string, printables } => {
+                //the printables go under the template, Format fills its holes with them
+                let holes = printables.len() as i32;
                 for i in printables {
-                    dump(&mut code, &mut lines, compile_expr(i))
+                    dump(&mut code, &mut spans, compile_expr(i)?)
                 }
                 code.push(OpCode::Constant(Value::String {
                     string,
                     printables: Vec::new(),
                 }));
-                lines.push(line);
+                spans.push(span);
+                code.push(OpCode::Format(holes));
+                spans.push(span);
             }
             _ => {
                 code.push(OpCode::Constant(x));
-                lines.push(line);
+                spans.push(span);
             }
         },
         Expr::Range {
             min,
             max,
             step,
-            line,
+            span,
         } => match step {
             Some(x) => {
-                dump(&mut code, &mut lines, compile_expr(*min));
-                dump(&mut code, &mut lines, compile_expr(*max));
-                dump(&mut code, &mut lines, compile_expr(*x));
+                dump(&mut code, &mut spans, compile_expr(*min)?);
+                dump(&mut code, &mut spans, compile_expr(*max)?);
+                dump(&mut code, &mut spans, compile_expr(*x)?);
                 code.push(OpCode::Range(true));
-                lines.push(line);
+                spans.push(span);
             }
             None => {
-                dump(&mut code, &mut lines, compile_expr(*min));
-                dump(&mut code, &mut lines, compile_expr(*max));
+                dump(&mut code, &mut spans, compile_expr(*min)?);
+                dump(&mut code, &mut spans, compile_expr(*max)?);
                 code.push(OpCode::Range(false));
-                lines.push(line);
+                spans.push(span);
             }
         },
         Expr::Unary {
             operator,
             expression,
+            span,
         } => {
-            dump(&mut code, &mut lines, compile_expr(*expression));
-            code.push(un(operator.tt));
-            lines.push(operator.line);
+            dump(&mut code, &mut spans, compile_expr(*expression)?);
+            code.push(un(&operator)?);
+            spans.push(span);
         }
         Expr::Variable(name) => {
             code.push(OpCode::Load(name.lexeme));
-            lines.push(name.line)
+            spans.push(name.span)
         }
-        Expr::Vec(vec) => {
+        Expr::Vec(vec, span) => {
             let len = vec.len();
             for i in vec {
-                dump(&mut code, &mut lines, compile_expr(i));
+                dump(&mut code, &mut spans, compile_expr(i)?);
             }
             code.push(OpCode::Iterable(len as i32));
-            lines.push(0);
+            spans.push(span);
+        }
+        Expr::Map(entries, span) => {
+            let len = entries.len();
+            for (key, value) in entries {
+                dump(&mut code, &mut spans, compile_expr(key)?);
+                dump(&mut code, &mut spans, compile_expr(value)?);
+            }
+            code.push(OpCode::Map(len as i32));
+            spans.push(span);
+        }
+        Expr::Index {
+            object,
+            index,
+            span,
+        } => {
+            dump(&mut code, &mut spans, compile_expr(*object)?);
+            dump(&mut code, &mut spans, compile_expr(*index)?);
+            code.push(OpCode::Index);
+            spans.push(span);
+        }
+        Expr::Get { object, name, span } => {
+            dump(&mut code, &mut spans, compile_expr(*object)?);
+            code.push(OpCode::GetField(name.lexeme));
+            spans.push(span);
+        }
+        Expr::Path { object, name, span } => {
+            dump(&mut code, &mut spans, compile_expr(*object)?);
+            code.push(OpCode::GetFn(name.lexeme));
+            spans.push(span);
+        }
+        Expr::Set {
+            target,
+            value,
+            operator,
+            span,
+        } => {
+            //m[i].x = y loads m and walks down to m[i] keeping every value and index
+            //on the stack, then puts y in and sets them back from the inside out
+            let mut places = Vec::new();
+            let mut target = *target;
+            let root = loop {
+                target = match target {
+                    Expr::Index {
+                        object,
+                        index,
+                        span,
+                    } => {
+                        places.push((Place::Index(index), span));
+                        *object
+                    }
+                    Expr::Get { object, name, span } => {
+                        places.push((Place::Field(name.lexeme), span));
+                        *object
+                    }
+                    root => break root,
+                }
+            };
+            let name = match root {
+                Expr::Variable(name) => name,
+                root => return Err(error(root.span(), "can only assign to a variable")),
+            };
+            places.reverse();
+            let depth = places.len();
+            code.push(OpCode::Load(name.lexeme.clone()));
+            spans.push(name.span);
+            let mut sets = Vec::new();
+            for (i, (place, span)) in places.into_iter().enumerate() {
+                //m[i] += x also needs the old m[i]
+                let get = i + 1 < depth || operator.is_some();
+                match place {
+                    Place::Index(index) => {
+                        dump(&mut code, &mut spans, compile_expr(*index)?);
+                        if get {
+                            code.push(OpCode::Duplicate(2));
+                            spans.push(span);
+                            code.push(OpCode::Index);
+                            spans.push(span);
+                        }
+                        sets.push((OpCode::SetIndex, span));
+                    }
+                    Place::Field(field) => {
+                        if get {
+                            code.push(OpCode::Duplicate(1));
+                            spans.push(span);
+                            code.push(OpCode::GetField(field.clone()));
+                            spans.push(span);
+                        }
+                        sets.push((OpCode::SetField(field), span));
+                    }
+                }
+            }
+            dump(&mut code, &mut spans, compile_expr(*value)?);
+            if let Some(operator) = &operator {
+                code.push(bin(operator)?);
+                spans.push(span);
+            }
+            for (set, span) in sets.into_iter().rev() {
+                code.push(set);
+                spans.push(span);
+            }
+            code.push(OpCode::Store(name.lexeme));
+            spans.push(name.span);
+        }
+        Expr::Struct { name, fields, span } => {
+            //the struct is a variable like any other, its fields go on top of it
+            code.push(OpCode::Load(name.lexeme));
+            spans.push(name.span);
+            let mut names = Vec::new();
+            for (field, value) in fields {
+                dump(&mut code, &mut spans, compile_expr(value)?);
+                names.push(field.lexeme);
+            }
+            code.push(OpCode::Construct(names));
+            spans.push(span);
+        }
+        Expr::Lambda { params, body, span } => {
+            dump(&mut code, &mut spans, function(None, params, *body, span)?)
+        }
+        Expr::Slice {
+            object,
+            start,
+            end,
+            step,
+            span,
+        } => {
+            dump(&mut code, &mut spans, compile_expr(*object)?);
+            //a part thats left out is None, the vm fills in the default
+            for part in [start, end, step] {
+                match part {
+                    Some(part) => dump(&mut code, &mut spans, compile_expr(*part)?),
+                    None => {
+                        code.push(OpCode::Constant(Value::None));
+                        spans.push(span);
+                    }
+                }
+            }
+            code.push(OpCode::Slice);
+            spans.push(span);
         }
     }
-    (code, lines)
+    Ok((code, spans))
 }
 
-pub fn bin(operator: TokenType) -> OpCode {
-    match operator {
+//one step on the way from a variable to what gets assigned
+enum Place {
+    Index(Box<Expr>),
+    Field(String),
+}
+
+pub fn bin(operator: &Token) -> Result<OpCode, KlangError> {
+    Ok(match operator.tt {
         TokenType::Plus => OpCode::Add,
         TokenType::Minus => OpCode::Subtract,
         TokenType::Star => OpCode::Multiply,
@@ -308,23 +579,54 @@ pub fn bin(operator: TokenType) -> OpCode {
         TokenType::GreaterEqual => OpCode::GreaterEqual,
         TokenType::And => OpCode::LogicalAnd,
         TokenType::Or => OpCode::LogicalOr,
-        _ => panic!("how did you even get here?"),
-    }
+        _ => return Err(error(operator.span, "how did you even get here?")),
+    })
 }
 
-pub fn un(operator: TokenType) -> OpCode {
-    match operator {
+pub fn un(operator: &Token) -> Result<OpCode, KlangError> {
+    Ok(match operator.tt {
         TokenType::Minus => OpCode::Negate,
         TokenType::Bang => OpCode::LogicalNot,
-        _ => panic!("how did you even get here?"),
+        _ => return Err(error(operator.span, "how did you even get here?")),
+    })
+}
+
+fn error(span: Span, msg: &str) -> KlangError {
+    Diagnostic::new(ErrorKind::CompilerError, msg, span).into()
+}
+
+//turns the breaks and continues meant for this loop into jumps.
+//body still ends with its Eof, which is where the loops last Jump goes
+fn patch_loop(
+    body: &mut [OpCode],
+    label: Option<Token>,
+    mut depth: i32,
+    continue_to: i32,
+    for_loop: bool,
+) {
+    let label = label.map(|label| label.lexeme);
+    let end = body.len() as i32 - 1;
+    for (i, op) in body.iter_mut().enumerate() {
+        let i = i as i32;
+        match op {
+            OpCode::Scope => depth += 1,
+            OpCode::EndScope => depth -= 1,
+            OpCode::Break(x) if x.is_none() || *x == label => {
+                *op = OpCode::Unwind(depth, end - i, for_loop);
+            }
+            OpCode::Continue(x) if x.is_none() || *x == label => {
+                *op = OpCode::Unwind(depth, continue_to - i, false);
+            }
+            _ => {}
+        }
     }
 }
 
-pub fn dump(main: &mut Vec<OpCode>, lines: &mut Vec<usize>, se: (Vec<OpCode>, Vec<usize>)) {
+pub fn dump(main: &mut Vec<OpCode>, spans: &mut Vec<Span>, se: (Vec<OpCode>, Vec<Span>)) {
     for i in se.0.into_iter() {
         main.push(i);
     }
     for i in se.1.into_iter() {
-        lines.push(i);
+        spans.push(i);
     }
 }
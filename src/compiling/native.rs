@@ -1,18 +1,20 @@
-use crate::interpreter::scanner::Value;
-use crate::KlangError;
+use crate::interpreter::scanner::{MapKey, Value};
 use rand::Rng;
 use std::fs::{File, OpenOptions};
 use std::io::{self, Read, Write};
 use std::path::Path;
 use std::time::{SystemTime, UNIX_EPOCH};
 
+//what a native returns: maybe a value, or a message saying what went wrong
+type NativeResult = Result<Option<Value>, String>;
+
 pub struct NativeFn {
     pub name: String,
     pub args: i32,
-    pub function: Box<dyn Fn(Vec<Value>) -> Option<Value>>,
+    pub function: Box<dyn Fn(Vec<Value>) -> NativeResult>,
 }
 impl NativeFn {
-    pub fn call(&self, args: Vec<Value>) -> Option<Value> {
+    pub fn call(&self, args: Vec<Value>) -> NativeResult {
         (self.function)(args)
     }
 }
@@ -24,18 +26,19 @@ pub fn create_natives() -> Vec<NativeFn> {
     natives.extend(time_natives());
     natives.extend(create_file_io_natives());
     natives.extend(vector_natives());
+    natives.extend(map_natives());
     natives.push(NativeFn {
         name: "read".to_string(),
         args: 0,
         function: Box::new(|_| {
             let mut input = String::new();
-            io::stdin()
-                .read_line(&mut input)
-                .expect("Failed to read line");
-            Some(Value::String {
+            if let Err(err) = io::stdin().read_line(&mut input) {
+                return error(format!("Failed to read line: {}", err).as_str());
+            }
+            Ok(Some(Value::String {
                 string: input.trim().to_string(),
                 printables: Vec::new(),
-            })
+            }))
         }),
     });
     natives
@@ -48,12 +51,9 @@ fn math_natives() -> Vec<NativeFn> {
         function: Box::new(|args| match args[0] {
             Value::Number(num) => {
                 let result = num.sin();
-                Some(Value::Number(result))
-            }
-            _ => {
-                error("can only use sin on a number!");
-                panic!()
+                Ok(Some(Value::Number(result)))
             }
+            _ => error("can only use sin on a number!"),
         }),
     });
     math_functions.push(NativeFn {
@@ -62,12 +62,9 @@ fn math_natives() -> Vec<NativeFn> {
         function: Box::new(|args| match args[0] {
             Value::Number(num) => {
                 let result = num.cos();
-                Some(Value::Number(result))
-            }
-            _ => {
-                error("can only use cos on a number!");
-                panic!()
+                Ok(Some(Value::Number(result)))
             }
+            _ => error("can only use cos on a number!"),
         }),
     });
     math_functions.push(NativeFn {
@@ -76,12 +73,9 @@ fn math_natives() -> Vec<NativeFn> {
         function: Box::new(|args| match args[0] {
             Value::Number(num) => {
                 let result = num.tan();
-                Some(Value::Number(result))
-            }
-            _ => {
-                error("can only use tan on a number!");
-                panic!()
+                Ok(Some(Value::Number(result)))
             }
+            _ => error("can only use tan on a number!"),
         }),
     });
     math_functions.push(NativeFn {
@@ -90,12 +84,9 @@ fn math_natives() -> Vec<NativeFn> {
         function: Box::new(|args| match args[0] {
             Value::Number(num) => {
                 let result = num.sqrt();
-                Some(Value::Number(result))
-            }
-            _ => {
-                error("can only use sqrt on a number!");
-                panic!()
+                Ok(Some(Value::Number(result)))
             }
+            _ => error("can only use sqrt on a number!"),
         }),
     });
     math_functions.push(NativeFn {
@@ -104,12 +95,9 @@ fn math_natives() -> Vec<NativeFn> {
         function: Box::new(|args| match (args[0].clone(), args[1].clone()) {
             (Value::Number(base), Value::Number(exponent)) => {
                 let result = base.powf(exponent);
-                Some(Value::Number(result))
-            }
-            _ => {
-                error("can only use pow on 2 numbers!");
-                panic!()
+                Ok(Some(Value::Number(result)))
             }
+            _ => error("can only use pow on 2 numbers!"),
         }),
     });
     math_functions.push(NativeFn {
@@ -118,12 +106,9 @@ fn math_natives() -> Vec<NativeFn> {
         function: Box::new(|args| match args[0] {
             Value::Number(num) => {
                 let result = num.ln();
-                Some(Value::Number(result))
-            }
-            _ => {
-                error("can only use ln on a number!");
-                panic!()
+                Ok(Some(Value::Number(result)))
             }
+            _ => error("can only use ln on a number!"),
         }),
     });
     math_functions.push(NativeFn {
@@ -132,12 +117,9 @@ fn math_natives() -> Vec<NativeFn> {
         function: Box::new(|args| match args[0] {
             Value::Number(num) => {
                 let result = num.log10();
-                Some(Value::Number(result))
-            }
-            _ => {
-                error("can only use log10 on a number!");
-                panic!()
+                Ok(Some(Value::Number(result)))
             }
+            _ => error("can only use log10 on a number!"),
         }),
     });
     math_functions.push(NativeFn {
@@ -146,51 +128,39 @@ fn math_natives() -> Vec<NativeFn> {
         function: Box::new(|args| match args[0] {
             Value::Number(num) => {
                 let result = num.round();
-                Some(Value::Number(result))
-            }
-            _ => {
-                error("can only use round on a number!");
-                panic!()
+                Ok(Some(Value::Number(result)))
             }
+            _ => error("can only use round on a number!"),
         }),
     });
     math_functions.push(NativeFn {
         name: "abs".to_string(),
         args: 1,
         function: Box::new(|args| match args[0] {
-            Value::Number(x) => Some(Value::Number(x.abs())),
-            _ => {
-                error("can only use abs on a number!");
-                panic!()
-            }
+            Value::Number(x) => Ok(Some(Value::Number(x.abs()))),
+            _ => error("can only use abs on a number!"),
         }),
     });
     math_functions.push(NativeFn {
         name: "min".to_string(),
         args: 2,
         function: Box::new(|args| match (args[0].clone(), args[1].clone()) {
-            (Value::Number(a), Value::Number(b)) => Some(Value::Number(a.min(b))),
-            _ => {
-                error("can only use min on 2 numbers!");
-                panic!()
-            }
+            (Value::Number(a), Value::Number(b)) => Ok(Some(Value::Number(a.min(b)))),
+            _ => error("can only use min on 2 numbers!"),
         }),
     });
     math_functions.push(NativeFn {
         name: "max".to_string(),
         args: 2,
         function: Box::new(|args| match (args[0].clone(), args[1].clone()) {
-            (Value::Number(a), Value::Number(b)) => Some(Value::Number(a.max(b))),
-            _ => {
-                error("can only use max on 2 numbers!");
-                panic!()
-            }
+            (Value::Number(a), Value::Number(b)) => Ok(Some(Value::Number(a.max(b)))),
+            _ => error("can only use max on 2 numbers!"),
         }),
     });
     math_functions.push(NativeFn {
         name: "pi".to_string(),
         args: 0,
-        function: Box::new(|_| Some(Value::Number(std::f64::consts::PI))),
+        function: Box::new(|_| Ok(Some(Value::Number(std::f64::consts::PI)))),
     });
     math_functions
 }
@@ -201,7 +171,7 @@ pub fn random_natives() -> Vec<NativeFn> {
         args: 0,
         function: Box::new(|_| {
             let mut rng = rand::thread_rng();
-            Some(Value::Number(rng.gen::<f64>()))
+            Ok(Some(Value::Number(rng.gen::<f64>())))
         }),
     });
     natives.push(NativeFn {
@@ -211,12 +181,9 @@ pub fn random_natives() -> Vec<NativeFn> {
             (Value::Number(min), Value::Number(max)) if min < max => {
                 let mut rng = rand::thread_rng();
                 let random_value = rng.gen_range(min..max);
-                Some(Value::Number(random_value))
-            }
-            _ => {
-                error("can only use random_range on 2 numbers!");
-                panic!()
+                Ok(Some(Value::Number(random_value)))
             }
+            _ => error("can only use random_range on 2 numbers!"),
         }),
     });
     natives.push(NativeFn {
@@ -224,7 +191,7 @@ pub fn random_natives() -> Vec<NativeFn> {
         args: 0,
         function: Box::new(|_| {
             let mut rng = rand::thread_rng();
-            Some(Value::Bool(rng.gen::<bool>()))
+            Ok(Some(Value::Bool(rng.gen::<bool>())))
         }),
     });
     natives
@@ -236,12 +203,12 @@ pub fn time_natives() -> Vec<NativeFn> {
         name: "time".to_string(),
         args: 0,
         function: Box::new(|_| {
-            Some(Value::Number(
+            Ok(Some(Value::Number(
                 SystemTime::now()
                     .duration_since(UNIX_EPOCH)
                     .unwrap()
                     .as_secs_f64(),
-            ))
+            )))
         }),
     });
     natives.push(NativeFn {
@@ -250,12 +217,9 @@ pub fn time_natives() -> Vec<NativeFn> {
         function: Box::new(|args| match args[0] {
             Value::Number(duration) if duration >= 0.0 => {
                 std::thread::sleep(std::time::Duration::from_secs_f64(duration));
-                None
-            }
-            _ => {
-                error("can only use sleep on a number!");
-                panic!()
+                Ok(None)
             }
+            _ => error("can only use sleep on a number!"),
         }),
     });
     natives
@@ -269,21 +233,17 @@ pub fn create_file_io_natives() -> Vec<NativeFn> {
         function: Box::new(|args| {
             if let Some(Value::String {
                 string: filename, ..
-            }) = args.get(0)
+            }) = args.first()
             {
                 match read_file(filename) {
-                    Ok(contents) => Some(Value::String {
+                    Ok(contents) => Ok(Some(Value::String {
                         string: contents,
                         printables: Vec::new(),
-                    }),
-                    Err(err) => {
-                        error(format!("Failed to read file: {}", err).as_str());
-                        panic!()
-                    }
+                    })),
+                    Err(err) => error(format!("Failed to read file: {}", err).as_str()),
                 }
             } else {
-                error("Invalid arguments for read_file");
-                panic!()
+                error("Invalid arguments for read_file")
             }
         }),
     });
@@ -298,18 +258,14 @@ pub fn create_file_io_natives() -> Vec<NativeFn> {
                 Some(Value::String {
                     string: contents, ..
                 }),
-            ) = (args.get(0), args.get(1))
+            ) = (args.first(), args.get(1))
             {
                 match write_file(filename, contents) {
-                    Ok(()) => None,
-                    Err(err) => {
-                        error(format!("Failed to write to file: {}", err).as_str());
-                        panic!()
-                    }
+                    Ok(()) => Ok(None),
+                    Err(err) => error(format!("Failed to write to file: {}", err).as_str()),
                 }
             } else {
-                error("Invalid arguments for write_file");
-                panic!()
+                error("Invalid arguments for write_file")
             }
         }),
     });
@@ -323,11 +279,11 @@ pub fn vector_natives() -> Vec<NativeFn> {
         args: 2,
         function: Box::new(
             |mut args| match (args.pop().unwrap(), args.pop().unwrap()) {
-                (Value::Number(index), Value::Vec(mut vec)) => Some(vec.remove(index as usize)),
-                _ => {
-                    error("expected a (vector, number)");
-                    panic!()
+                (index, Value::Vec(mut vec)) => {
+                    let index = position(&index, vec.len())?;
+                    Ok(Some(vec.remove(index)))
                 }
+                _ => error("expected a (vector, number)"),
             },
         ),
     });
@@ -335,18 +291,14 @@ pub fn vector_natives() -> Vec<NativeFn> {
         name: "set".to_string(),
         args: 3,
         function: Box::new(|mut args| {
-            if let Value::Number(index) = args.pop().unwrap() {
-                let value = args.pop().unwrap();
-                if let Value::Vec(mut vec) = args.pop().unwrap() {
-                    vec[index as usize] = value;
-                    Some(Value::Vec(vec))
-                } else {
-                    error("expected a (vector, value, index)");
-                    panic!()
-                }
+            let index = args.pop().unwrap();
+            let value = args.pop().unwrap();
+            if let Value::Vec(mut vec) = args.pop().unwrap() {
+                let index = position(&index, vec.len())?;
+                vec[index] = value;
+                Ok(Some(Value::Vec(vec)))
             } else {
-                error("expected a (vector, value, index)");
-                panic!()
+                error("expected a (vector, value, index)")
             }
         }),
     });
@@ -355,14 +307,15 @@ pub fn vector_natives() -> Vec<NativeFn> {
         args: 2,
         function: Box::new(
             |mut args| match (args.pop().unwrap(), args.pop().unwrap()) {
-                (Value::Number(index), Value::Vec(mut vec)) => {
-                    vec.remove(index as usize);
-                    Some(Value::Vec(vec))
-                }
-                _ => {
-                    error("expected a (vector, number)");
-                    panic!()
+                (index, Value::Vec(mut vec)) => {
+                    vec.remove(position(&index, vec.len())?);
+                    Ok(Some(Value::Vec(vec)))
                 }
+                (key, Value::Map(mut map)) => match map.remove(&MapKey::new(&key)?) {
+                    Some(_) => Ok(Some(Value::Map(map))),
+                    None => error(&format!("the map has no key {key}")),
+                },
+                _ => error("expected a (vector, number) or a (map, key)"),
             },
         ),
     });
@@ -370,27 +323,102 @@ pub fn vector_natives() -> Vec<NativeFn> {
         name: "insert".to_string(),
         args: 3,
         function: Box::new(|mut args| {
-            if let Value::Number(index) = args.pop().unwrap() {
-                let value = args.pop().unwrap();
-                if let Value::Vec(mut vec) = args.pop().unwrap() {
-                    vec.insert(index as usize, value);
-                    Some(Value::Vec(vec))
-                } else {
-                    error("expected a (vector, value, index)");
-                    panic!()
-                }
+            let index = args.pop().unwrap();
+            let value = args.pop().unwrap();
+            if let Value::Vec(mut vec) = args.pop().unwrap() {
+                //-1 goes before the last one like everywhere else, append with the length
+                let index = bound(&index, vec.len())?;
+                vec.insert(index, value);
+                Ok(Some(Value::Vec(vec)))
             } else {
-                error("expected a (vector, value, index)");
-                panic!()
+                error("expected a (vector, value, index)")
             }
         }),
     });
 
     natives
 }
+pub fn map_natives() -> Vec<NativeFn> {
+    let mut natives: Vec<NativeFn> = Vec::new();
+    natives.push(NativeFn {
+        name: "keys".to_string(),
+        args: 1,
+        function: Box::new(|mut args| match args.pop().unwrap() {
+            Value::Map(map) => Ok(Some(Value::Vec(
+                map.into_keys().map(|key| key.value()).collect(),
+            ))),
+            _ => error("expected a (map)"),
+        }),
+    });
+    natives.push(NativeFn {
+        name: "values".to_string(),
+        args: 1,
+        function: Box::new(|mut args| match args.pop().unwrap() {
+            Value::Map(map) => Ok(Some(Value::Vec(map.into_values().collect()))),
+            _ => error("expected a (map)"),
+        }),
+    });
+    natives.push(NativeFn {
+        name: "has".to_string(),
+        args: 2,
+        function: Box::new(
+            |mut args| match (args.pop().unwrap(), args.pop().unwrap()) {
+                (key, Value::Map(map)) => {
+                    Ok(Some(Value::Bool(map.contains_key(&MapKey::new(&key)?))))
+                }
+                _ => error("expected a (map, key)"),
+            },
+        ),
+    });
+    natives.push(NativeFn {
+        name: "len".to_string(),
+        args: 1,
+        function: Box::new(|mut args| {
+            let len = match args.pop().unwrap() {
+                Value::Map(map) => map.len(),
+                Value::Vec(vec) => vec.len(),
+                Value::String { string, .. } => string.chars().count(),
+                _ => return error("expected a (map), (vector) or (string)"),
+            };
+            Ok(Some(Value::Number(len as f64)))
+        }),
+    });
+
+    natives
+}
+
+//where index points in something of that length, negative ones count from the end
+pub fn position(index: &Value, len: usize) -> Result<usize, String> {
+    resolve(index, len, len.checked_sub(1))
+}
+
+//same but for the start and end of a slice, which can also be right after the last one
+pub fn bound(index: &Value, len: usize) -> Result<usize, String> {
+    resolve(index, len, Some(len))
+}
+
+fn resolve(index: &Value, len: usize, last: Option<usize>) -> Result<usize, String> {
+    let index = match index {
+        Value::Number(x) if x.fract() == 0.0 => *x,
+        Value::Number(x) => return Err(format!("index must be a whole number but its {x}")),
+        x => return Err(format!("index must be a number but its {x}")),
+    };
+    let position = if index < 0.0 {
+        len as f64 + index
+    } else {
+        index
+    };
+    match last {
+        Some(last) if position >= 0.0 && position <= last as f64 => Ok(position as usize),
+        _ => Err(format!(
+            "index {index} is out of bounds, the length is {len}"
+        )),
+    }
+}
 
-fn error(msg: &str) {
-    KlangError::error(KlangError::RuntimeError, msg, 0, "")
+//natives only know what went wrong, the vm adds where it happened
+fn error(msg: &str) -> NativeResult {
+    Err(msg.to_string())
 }
 fn read_file(filename: &str) -> io::Result<String> {
     let path = Path::new(filename);
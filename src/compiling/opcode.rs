@@ -5,15 +5,16 @@ use std::fmt;
 pub enum OpCode {
     Constant(Value),         //Load a constant value onto the stack
     Store(String), // Store the value from the top of the stack into the variable in the hashtable.
-    Load(String),  //Load the value of the variable from the hashtable onto the stack
-    Add,           // Performs addition on the last two values on the stack.
-    Subtract,      // Performs subtraction on the last two values on the stack.
-    Multiply,      // Performs multiplication on the last two values on the stack.
-    Divide,        // Performs division on the last two values on the stack.
-    Modulo,        // sex
-    EqualEqual,    // Compares equality between the last two values on the stack.
-    NotEqual,      // Compares inequality between the last two values on the stack.
-    Less,          // Checks if the second-to-last value on the stack is less than the last value.
+    Let(String), // Store, for let, fn, struct and for, so the compiler can tell what a function makes itself
+    Load(String), //Load the value of the variable from the hashtable onto the stack
+    Add,         // Performs addition on the last two values on the stack.
+    Subtract,    // Performs subtraction on the last two values on the stack.
+    Multiply,    // Performs multiplication on the last two values on the stack.
+    Divide,      // Performs division on the last two values on the stack.
+    Modulo,      // sex
+    EqualEqual,  // Compares equality between the last two values on the stack.
+    NotEqual,    // Compares inequality between the last two values on the stack.
+    Less,        // Checks if the second-to-last value on the stack is less than the last value.
     LessEqual, // Checks if the second-to-last value on the stack is less than or equal to the last value.
     Greater,   // Checks if the second-to-last value on the stack is greater than the last value.
     GreaterEqual, // Checks if the second-to-last value on the stack is greater than or equal to the last value.
@@ -23,17 +24,35 @@ pub enum OpCode {
     Negate,       // Negates the last numeric value on the stack.
     Jump(i32),    // Unconditionally jumps to a specified instruction address.
     JumpIf(i32, bool), // Jumps to a specified instruction address if the last value on the stack is true.
-    Call(String),      // Calls a function at a specified instruction address.
+    Call(i32),         // Calls the function under that many arguments on the stack.
     NativeCall(String, i32), // Calls a native function or external function.
-    Print,             // Prints the last value on the stack to the console or output stream.
+    Print,             // Prints the string on top of the stack to the console or output stream.
+    Format(i32), // pops a string and the x values under it and pushes the string with its holes filled
     Range(bool),
     Scope,
     EndScope,
-    EndFn,
     Return(bool),
-    For,
-    Fn,
+    For,     // takes the next value off the end of what Iterate made, or leaves the loop
+    Iterate, // turns what a for loop goes over into its values, last first, before the loop starts
+    Entries, // turns what a for loop goes over into [key, value] pairs, before the loop starts
+    Unpack(String, String), // stores the [key, value] on the stack into two variables
+    Fn(Option<String>, Vec<String>, Vec<(String, bool)>, i32), // makes a function (name, params, variables it uses from outside and if only from outside) out of the next x opcodes and skips them
     Iterable(i32),
+    Map(i32),                    // makes a map out of x keys and values
+    Index,          // pops an index and a vector (or string) and pushes what is at that index
+    SetIndex, // pops a value, an index and a vector and pushes the vector with the value put in
+    Duplicate(i32), // pushes a copy of the top x values on the stack
+    Slice,    // pops a step, an end, a start and a vector (or string) and pushes that part of it
+    Struct(String, Vec<String>), // makes a struct (name, fields)
+    Construct(Vec<String>), // pops a value for each field and the struct under them and makes an instance
+    GetField(String),       // pops an instance and pushes its field
+    SetField(String), // pops a value and an instance and pushes the instance with the field changed
+    Impl(Vec<String>), // pops a function for each name and the struct under them and gives them to the struct
+    GetFn(String),     // pops a struct and pushes its function
+    Invoke(String, i32), // calls the method under that many arguments on the value under them
+    Break(Option<String>), // placeholder until the loop its in turns it into an Unwind
+    Continue(Option<String>), // same
+    Unwind(i32, i32, bool), // closes that many scopes, pops the for loops leftovers if true, then jumps
     Eof,
 }
 
@@ -42,6 +61,7 @@ impl fmt::Display for OpCode {
         match self {
             OpCode::Constant(constant) => write!(f, "Constant {}", constant),
             OpCode::Store(name) => write!(f, "Store {}", name),
+            OpCode::Let(name) => write!(f, "Let {}", name),
             OpCode::Load(name) => write!(f, "Load {}", name),
             OpCode::Add => write!(f, "Add"),
             OpCode::Subtract => write!(f, "Subtract"),
@@ -63,14 +83,42 @@ impl fmt::Display for OpCode {
             OpCode::Call(x) => write!(f, "Call {}", x),
             OpCode::NativeCall(x, y) => write!(f, "NativeCall {} {}", x, y),
             OpCode::Print => write!(f, "Print"),
+            OpCode::Format(x) => write!(f, "Format {}", x),
             OpCode::Range(x) => write!(f, "Range {}", x),
             OpCode::Scope => write!(f, "Scope"),
             OpCode::EndScope => write!(f, "EndScope"),
-            OpCode::EndFn => write!(f, "EndFn"),
             OpCode::Return(x) => write!(f, "Return {}", x),
             OpCode::For => write!(f, "For"),
-            OpCode::Fn => write!(f, "Fn"),
+            OpCode::Iterate => write!(f, "Iterate"),
+            OpCode::Entries => write!(f, "Entries"),
+            OpCode::Unpack(x, y) => write!(f, "Unpack {} {}", x, y),
+            OpCode::Fn(x, y, z, w) => write!(
+                f,
+                "Fn {} ({}) [{}] {}",
+                x.as_deref().unwrap_or(""),
+                y.join(", "),
+                z.iter()
+                    .map(|(name, _)| name.as_str())
+                    .collect::<Vec<_>>()
+                    .join(", "),
+                w
+            ),
             OpCode::Iterable(x) => write!(f, "Iterable {}", x),
+            OpCode::Map(x) => write!(f, "Map {}", x),
+            OpCode::Index => write!(f, "Index"),
+            OpCode::SetIndex => write!(f, "SetIndex"),
+            OpCode::Duplicate(x) => write!(f, "Duplicate {}", x),
+            OpCode::Slice => write!(f, "Slice"),
+            OpCode::Struct(x, y) => write!(f, "Struct {} {{{}}}", x, y.join(", ")),
+            OpCode::Construct(x) => write!(f, "Construct {{{}}}", x.join(", ")),
+            OpCode::GetField(x) => write!(f, "GetField {}", x),
+            OpCode::SetField(x) => write!(f, "SetField {}", x),
+            OpCode::Impl(x) => write!(f, "Impl {}", x.join(", ")),
+            OpCode::GetFn(x) => write!(f, "GetFn {}", x),
+            OpCode::Invoke(x, y) => write!(f, "Invoke {} {}", x, y),
+            OpCode::Break(x) => write!(f, "Break {}", x.as_deref().unwrap_or("")),
+            OpCode::Continue(x) => write!(f, "Continue {}", x.as_deref().unwrap_or("")),
+            OpCode::Unwind(x, y, z) => write!(f, "Unwind {} {} {}", x, y, z),
             OpCode::Eof => write!(f, "Eof"),
         }
     }
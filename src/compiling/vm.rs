@@ -1,155 +1,253 @@
 use super::{
     compiler::Chunk,
-    native::{create_natives, NativeFn},
+    native::{bound, create_natives, position, NativeFn},
     opcode::OpCode,
 };
-use crate::interpreter::scanner::{TokenType, Value};
-use crate::KlangError;
-use std::collections::HashMap;
-pub struct VM<'a> {
+use crate::diagnostic::Diagnostic;
+use crate::error::{ErrorKind, KlangError};
+use crate::interpreter::scanner::{
+    fill_holes, Env, Function, Instance, MapKey, Slot, StructType, TokenType, Value,
+};
+use std::cell::RefCell;
+use std::collections::{BTreeMap, HashMap};
+use std::rc::Rc;
+
+//how many calls can be inside each other before we give up
+const MAX_FRAMES: usize = 10000;
+
+pub struct VM {
     pub chunk: Chunk,
-    pub global: Scope,
+    pub scopes: Vec<Scope>, //the scopes the running code can see, the global one first
+    pub frames: Vec<Frame>, //the functions that are waiting for a call to return
     pub index: i32,
-    pub filename: &'a str,
-    pub functions: HashMap<String, (Vec<OpCode>, Vec<String>)>,
     pub native: Vec<NativeFn>,
 }
 
-impl<'a> VM<'a> {
-    pub fn new(chunk: Chunk, filename: &'a str) -> VM<'a> {
+impl VM {
+    pub fn new(chunk: Chunk) -> VM {
         VM {
             chunk,
-            global: Scope::new(),
+            scopes: vec![Scope::new()],
+            frames: Vec::new(),
             index: 0,
-            filename,
-            functions: HashMap::new(),
             native: create_natives(),
         }
     }
-    pub fn run(&mut self) {
+    pub fn run(&mut self) -> Result<(), KlangError> {
         //executes the code on the chunk
         while self.index < self.chunk.code.len() as i32 {
-            self.once();
+            self.once()?;
             self.index += 1;
         }
+        Ok(())
     }
-    pub fn once(&mut self) {
-        // println!("{:?}", self.global);
+    pub fn once(&mut self) -> Result<(), KlangError> {
         match self.chunk.code[self.index as usize].clone() {
             OpCode::Constant(x) => self.push(x),
-            OpCode::Store(x) => self.set_var(x),
+            OpCode::Store(x) | OpCode::Let(x) => self.set_var(x),
             OpCode::Load(x) => {
-                let var = match VM::get_var(&x, &mut self.global).0 {
+                let var = match self.get_var(&x) {
                     Some(x) => x,
                     None => {
-                        self.error(format!("variable \"{x}\" do not exist").as_str());
-                        panic!()
+                        return Err(self
+                            .diagnostic(format!("variable \"{x}\" do not exist").as_str())
+                            .help(format!("declare it first using let {x} = ...;").as_str())
+                            .into())
                     }
                 };
                 self.push(var);
             }
-            OpCode::Add => self.bin_op(TokenType::Plus),
-            OpCode::Subtract => self.bin_op(TokenType::Minus),
-            OpCode::Multiply => self.bin_op(TokenType::Star),
-            OpCode::Divide => self.bin_op(TokenType::Slash),
-            OpCode::Modulo => self.bin_op(TokenType::Modulo),
-            OpCode::EqualEqual => self.bin_op(TokenType::EqualEqual),
-            OpCode::NotEqual => self.bin_op(TokenType::BangEqual),
-            OpCode::Less => self.bin_op(TokenType::Less),
-            OpCode::LessEqual => self.bin_op(TokenType::LessEqual),
-            OpCode::Greater => self.bin_op(TokenType::Greater),
-            OpCode::GreaterEqual => self.bin_op(TokenType::GreaterEqual),
-            OpCode::LogicalAnd => self.bin_op(TokenType::And),
-            OpCode::LogicalOr => self.bin_op(TokenType::Or),
-            OpCode::LogicalNot => self.un_op(TokenType::Bang),
-            OpCode::Negate => self.un_op(TokenType::Minus),
-            OpCode::Jump(x) => {
-                if self.index + x > self.chunk.code.len() as i32 {
-                    self.error("cannot jump out of bounds like ur dad jumped out of the 50th story window bozo");
-                }
-                self.index += x;
-            }
+            OpCode::Add => self.bin_op(TokenType::Plus)?,
+            OpCode::Subtract => self.bin_op(TokenType::Minus)?,
+            OpCode::Multiply => self.bin_op(TokenType::Star)?,
+            OpCode::Divide => self.bin_op(TokenType::Slash)?,
+            OpCode::Modulo => self.bin_op(TokenType::Modulo)?,
+            OpCode::EqualEqual => self.bin_op(TokenType::EqualEqual)?,
+            OpCode::NotEqual => self.bin_op(TokenType::BangEqual)?,
+            OpCode::Less => self.bin_op(TokenType::Less)?,
+            OpCode::LessEqual => self.bin_op(TokenType::LessEqual)?,
+            OpCode::Greater => self.bin_op(TokenType::Greater)?,
+            OpCode::GreaterEqual => self.bin_op(TokenType::GreaterEqual)?,
+            OpCode::LogicalAnd => self.bin_op(TokenType::And)?,
+            OpCode::LogicalOr => self.bin_op(TokenType::Or)?,
+            OpCode::LogicalNot => self.un_op(TokenType::Bang)?,
+            OpCode::Negate => self.un_op(TokenType::Minus)?,
+            OpCode::Jump(x) => self.jump(x)?,
             OpCode::JumpIf(x, t) => {
                 if t {
                     if let Value::Bool(true) = match self.pop() {
                         Some(x) => x,
-                        None => {
-                            self.error("stack overflow (cant pop an empty stack)");
-                            panic!()
-                        }
+                        None => return Err(self.error("stack overflow (cant pop an empty stack)")),
                     } {
-                        if self.index + x > self.chunk.code.len() as i32 {
-                            self.error("cannot jump out of bounds like ur dad jumped out of the 50th story window bozo");
-                        }
-                        self.index += x;
+                        self.jump(x)?;
                     }
-                } else if let Value::Bool(true) = self.top() {
-                    if self.index + x > self.chunk.code.len() as i32 {
-                        self.error("cannot jump out of bounds like ur dad jumped out of the 50th story window bozo");
-                    }
-                    self.index += x;
+                } else if let Value::Bool(true) = self.top()? {
+                    self.jump(x)?;
                 }
             }
-            OpCode::Call(x) => {
-                self.call(x, self.index);
-            }
-            OpCode::NativeCall(x, y) => self.native_call(x, y),
-            OpCode::Print => self.print(),
-            OpCode::Range(x) => self.range(x),
+            OpCode::Call(x) => self.call(x)?,
+            OpCode::NativeCall(x, y) => self.native_call(x, y)?,
+            OpCode::Print => self.print()?,
+            OpCode::Format(x) => self.format(x)?,
+            OpCode::Range(x) => self.range(x)?,
             OpCode::Scope => self.create_inner(),
             OpCode::EndScope => self.close_inner(),
-            OpCode::EndFn => {}
             OpCode::Return(x) => {
-                if x {
-                    let val = match self.pop() {
-                        Some(x) => x,
-                        None => Value::None,
-                    };
-                    let mut counter = 1;
-                    while !matches!(self.chunk.code[self.index as usize], OpCode::EndFn) {
-                        self.index += 1;
-                        if matches!(self.chunk.code[self.index as usize], OpCode::Scope) {
-                            counter -= 1;
-                        }
-                        if matches!(self.chunk.code[self.index as usize], OpCode::EndScope) {
-                            counter += 1;
-                        }
-                    }
-                    for _ in 0..counter {
-                        self.close_inner()
-                    }
-                    self.push(val);
-                } else {
-                    let mut counter = 1;
-                    while !matches!(self.chunk.code[self.index as usize], OpCode::EndFn) {
-                        self.index += 1;
-                        if matches!(self.chunk.code[self.index as usize], OpCode::Scope) {
-                            counter -= 1;
-                        }
-                        if matches!(self.chunk.code[self.index as usize], OpCode::EndScope) {
-                            counter += 1;
+                let val = if x { self.pop() } else { None };
+                //going back to the caller also throws away every scope the function opened
+                let frame = match self.frames.pop() {
+                    Some(frame) => frame,
+                    None => return Err(self.error("cant return outside of a function")),
+                };
+                self.scopes = frame.scopes;
+                self.index = frame.index;
+                self.push(val.unwrap_or(Value::None));
+            }
+            OpCode::For => self.for_loop()?,
+            OpCode::Iterate => match self.pop() {
+                Some(x) => {
+                    let mut values = self.iterate(x)?;
+                    values.reverse(); //For pops them off the end
+                    self.push(Value::Vec(values));
+                }
+                None => return Err(self.error("invalid witewabwe!")),
+            },
+            OpCode::Entries => match self.pop() {
+                Some(x) => {
+                    let entries = self.entries(x)?;
+                    self.push(Value::Vec(entries));
+                }
+                None => return Err(self.error("invalid witewabwe!")),
+            },
+            OpCode::Unpack(key, value) => match self.pop() {
+                Some(Value::Vec(mut pair)) if pair.len() == 2 => {
+                    self.push(pair.remove(0));
+                    self.set_var(key);
+                    self.push(pair.remove(0));
+                    self.set_var(value);
+                }
+                _ => return Err(self.error("ksang made a little oopsy")),
+            },
+            OpCode::Fn(name, params, free, len) => {
+                //the global scope is always there, so it isnt captured
+                let mut captured = Vec::new();
+                for (name, outside) in free {
+                    let slot = self.scopes[1..]
+                        .iter()
+                        .rev()
+                        .find_map(|scope| scope.callframe.borrow().get(&name).cloned());
+                    let slot = match slot {
+                        Some(slot) => slot,
+                        //made after the function in the scope around it (like a fn below it),
+                        //its slot is made now and the let fills it in
+                        None if outside
+                            && self.scopes.len() > 1
+                            && !self.scopes[0].callframe.borrow().contains_key(&name) =>
+                        {
+                            let slot: Slot = Rc::new(RefCell::new(None));
+                            let scope = self.scopes.last().unwrap();
+                            scope
+                                .callframe
+                                .borrow_mut()
+                                .insert(name.clone(), slot.clone());
+                            slot
                         }
+                        None => continue,
+                    };
+                    captured.push((name, slot));
+                }
+                self.push(Value::Function(Function {
+                    name,
+                    params,
+                    start: self.index as usize + 1,
+                    captured: captured.into(),
+                }));
+                self.jump(len)?;
+            }
+            OpCode::Iterable(x) => self.iterable(x)?,
+            OpCode::Map(x) => self.map(x)?,
+            OpCode::Index => self.index()?,
+            OpCode::SetIndex => self.set_index()?,
+            OpCode::Slice => self.slice()?,
+            OpCode::Struct(name, fields) => self.push(Value::Type(Rc::new(StructType {
+                name,
+                fields,
+                methods: RefCell::new(HashMap::new()),
+            }))),
+            OpCode::Construct(names) => self.construct(names)?,
+            OpCode::Impl(names) => self.implement(names)?,
+            OpCode::GetFn(name) => {
+                let kind = match self.pop() {
+                    Some(Value::Type(kind)) => kind,
+                    Some(x) => {
+                        return Err(self
+                            .diagnostic(&format!("cant use :: on {x}"))
+                            .note("only structs have functions, like Point::new")
+                            .into())
                     }
-                    for _ in 0..counter {
-                        self.close_inner()
-                    }
+                    None => return Err(self.error("stack overflow (cant pop an empty stack)")),
+                };
+                let function = kind.methods.borrow().get(&name).cloned();
+                match function {
+                    Some(function) => self.push(Value::Function(function)),
+                    None => return Err(self.no_method(&kind, &name)),
                 }
             }
-            OpCode::For => self.for_loop(),
-            OpCode::Fn => self.function(),
-            OpCode::Iterable(x) => self.iterable(x),
+            OpCode::Invoke(name, x) => self.invoke(name, x)?,
+            OpCode::GetField(name) => {
+                let instance = self.instance(&name)?;
+                let field = self.field(&instance, &name)?;
+                self.push(instance.fields[field].clone());
+            }
+            OpCode::SetField(name) => {
+                let value = match self.pop() {
+                    Some(x) => x,
+                    None => return Err(self.error("stack overflow (cant pop an empty stack)")),
+                };
+                let mut instance = self.instance(&name)?;
+                let field = self.field(&instance, &name)?;
+                instance.fields[field] = value;
+                self.push(Value::Struct(instance));
+            }
+            OpCode::Duplicate(x) => {
+                let scope = self.scopes.last_mut().unwrap();
+                if scope.stack.len() < x as usize {
+                    return Err(self.error("stack overflow (cant pop an empty stack)"));
+                }
+                let top = scope.stack[scope.stack.len() - x as usize..].to_vec();
+                scope.stack.extend(top);
+            }
+            OpCode::Unwind(scopes, x, pop) => {
+                for _ in 0..scopes {
+                    self.close_inner();
+                }
+                if pop {
+                    self.pop(); //what the for loop had left to go through
+                }
+                self.jump(x)?;
+            }
+            OpCode::Break(_) | OpCode::Continue(_) => {
+                return Err(self.error("ksang made a little oopsy (a loop jump was never patched)"))
+            }
             OpCode::Eof => {}
         }
+        Ok(())
+    }
+    fn jump(&mut self, x: i32) -> Result<(), KlangError> {
+        if self.index + x > self.chunk.code.len() as i32 {
+            return Err(self.error(
+                "cannot jump out of bounds like ur dad jumped out of the 50th story window bozo",
+            ));
+        }
+        self.index += x;
+        Ok(())
     }
-    fn iterable(&mut self, x: i32) {
+    fn iterable(&mut self, x: i32) -> Result<(), KlangError> {
         let mut vec: Vec<Value> = Vec::with_capacity(x as usize);
         for _ in 0..x {
             vec.push(match self.pop() {
                 Some(x) => x,
-                None => {
-                    self.error("stack overflow (cant pop an empty stack)");
-                    panic!()
-                }
+                None => return Err(self.error("stack overflow (cant pop an empty stack)")),
             });
         }
         let mut vec1: Vec<Value> = Vec::with_capacity(x as usize);
@@ -157,133 +255,372 @@ impl<'a> VM<'a> {
             vec1.push(i);
         }
         self.push(Value::Vec(vec1));
+        Ok(())
     }
-    fn function(&mut self) {
-        self.index += 1; //consume fn
-        let mut args: Vec<String> = Vec::new();
-        while match self.chunk.code[self.index as usize].clone() {
-            OpCode::Store(x) => {
-                args.push(x);
-                true
-            }
-            _ => false,
-        } {
-            self.index += 1; //consume arg
+    fn map(&mut self, x: i32) -> Result<(), KlangError> {
+        let mut entries: Vec<(Value, Value)> = Vec::with_capacity(x as usize);
+        for _ in 0..x {
+            let (value, key) = self.pop2()?;
+            entries.push((key, value));
         }
-        let mut bytes: Vec<OpCode> = Vec::new();
-        self.index += 1;
-        let mut counter = 1;
-        while counter != 0 {
-            bytes.push(self.chunk.code[self.index as usize].clone());
-            self.index += 1;
-            if matches!(self.chunk.code[self.index as usize], OpCode::EndScope) {
-                counter -= 1;
+        //from the bottom of the stack up, so a key thats written twice keeps the last value
+        let mut map = BTreeMap::new();
+        for (key, value) in entries.into_iter().rev() {
+            map.insert(self.key(&key)?, value);
+        }
+        self.push(Value::Map(map));
+        Ok(())
+    }
+    fn key(&self, key: &Value) -> Result<MapKey, KlangError> {
+        MapKey::new(key).map_err(|x| self.error(&x))
+    }
+    fn index(&mut self) -> Result<(), KlangError> {
+        let (index, value) = self.pop2()?;
+        let element = match value {
+            Value::Map(mut map) => match map.remove(&self.key(&index)?) {
+                Some(x) => x,
+                None => {
+                    return Err(self
+                        .diagnostic(&format!("the map has no key {index}"))
+                        .help("check for it first with std::has(map, key)")
+                        .into())
+                }
+            },
+            Value::Vec(mut vec) => {
+                let index = position(&index, vec.len()).map_err(|x| self.error(&x))?;
+                vec.swap_remove(index)
+            }
+            Value::String { string, .. } => {
+                let mut chars: Vec<char> = string.chars().collect();
+                let index = position(&index, chars.len()).map_err(|x| self.error(&x))?;
+                Value::String {
+                    string: chars.swap_remove(index).to_string(),
+                    printables: Vec::new(),
+                }
             }
-            if matches!(self.chunk.code[self.index as usize], OpCode::Scope) {
-                counter += 1;
+            x => {
+                return Err(self
+                    .diagnostic(&format!("cant index into {x}"))
+                    .note("only vectors, strings and maps can be indexed")
+                    .into())
             }
+        };
+        self.push(element);
+        Ok(())
+    }
+    fn slice(&mut self) -> Result<(), KlangError> {
+        let step = match self.pop() {
+            Some(Value::None) => 1,
+            Some(x) => {
+                self.push(x);
+                self.range_bound("step")?
+            }
+            None => return Err(self.error("stack overflow (cant pop an empty stack)")),
+        };
+        if step < 1 {
+            return Err(self.error(&format!("slice step must be at least 1 but its {step}")));
         }
-        self.index += 1;
-        match self.chunk.code[self.index as usize].clone() {
-            OpCode::Store(x) => self.functions.insert(x, (bytes, args)),
-            _ => {
-                self.error("ksang made a little oopsy");
-                panic!();
+        let (end, start) = self.pop2()?;
+        let value = match self.pop() {
+            Some(x) => x,
+            None => return Err(self.error("stack overflow (cant pop an empty stack)")),
+        };
+        let mut elements = match value {
+            Value::Vec(_) | Value::String { .. } => self.iterate(value.clone())?,
+            x => {
+                return Err(self
+                    .diagnostic(&format!("cant slice {x}"))
+                    .note("only vectors and strings can be sliced")
+                    .into())
             }
         };
+        let len = elements.len();
+        let part = |x: Value, default: usize| match x {
+            Value::None => Ok(default),
+            x => bound(&x, len),
+        };
+        let start = part(start, 0).map_err(|x| self.error(&x))?;
+        let end = part(end, len).map_err(|x| self.error(&x))?;
+        //like ranges, a slice that starts after its end is empty
+        let elements: Vec<Value> = elements
+            .drain(start.min(end)..end)
+            .step_by(step as usize)
+            .collect();
+        self.push(match value {
+            Value::Vec(_) => Value::Vec(elements),
+            _ => Value::String {
+                string: elements.into_iter().map(|x| x.to_string()).collect(),
+                printables: Vec::new(),
+            },
+        });
+        Ok(())
     }
-    fn range(&mut self, cstep: bool) {
-        if cstep {
-            let step = match self.pop() {
-                Some(Value::Number(x)) => x,
-                _ => {
-                    self.error("step is not a number");
-                    panic!()
-                }
-            };
-            let end = match self.pop() {
-                Some(Value::Number(x)) => x,
-                _ => {
-                    self.error("end is not a number");
-                    panic!()
-                }
-            };
-            let start = match self.pop() {
-                Some(Value::Number(x)) => x,
-                _ => {
-                    self.error("start is not a number");
-                    panic!()
-                }
-            };
-            let mut vec: Vec<Value> = Vec::new();
-            for i in (start as usize..end as usize).step_by(step as usize) {
-                vec.push(Value::Number(i as f64));
+    fn set_index(&mut self) -> Result<(), KlangError> {
+        let value = match self.pop() {
+            Some(x) => x,
+            None => return Err(self.error("stack overflow (cant pop an empty stack)")),
+        };
+        let (index, vec) = self.pop2()?;
+        match vec {
+            Value::Map(mut map) => {
+                map.insert(self.key(&index)?, value);
+                self.push(Value::Map(map));
+                Ok(())
             }
-            self.push(Value::Vec(vec))
-        } else {
-            let end = match self.pop() {
-                Some(Value::Number(x)) => x,
-                _ => {
-                    self.error("end is not a number");
-                    panic!()
-                }
-            };
-            let start = match self.pop() {
-                Some(Value::Number(x)) => x,
-                _ => {
-                    self.error("start is not a number");
-                    panic!()
+            Value::Vec(mut vec) => {
+                let index = position(&index, vec.len()).map_err(|x| self.error(&x))?;
+                vec[index] = value;
+                self.push(Value::Vec(vec));
+                Ok(())
+            }
+            x => Err(self
+                .diagnostic(&format!("cant assign into an index of {x}"))
+                .note("only the elements of vectors and maps can be changed")
+                .into()),
+        }
+    }
+    fn construct(&mut self, names: Vec<String>) -> Result<(), KlangError> {
+        let mut values: Vec<Value> = Vec::with_capacity(names.len());
+        for _ in 0..names.len() {
+            match self.pop() {
+                Some(x) => values.insert(0, x),
+                None => return Err(self.error("stack overflow (cant pop an empty stack)")),
+            }
+        }
+        let kind = match self.pop() {
+            Some(Value::Type(kind)) => kind,
+            Some(x) => {
+                return Err(self
+                    .diagnostic(&format!("{x} is not a struct"))
+                    .help("declare it first using struct Name { field, ... }")
+                    .into())
+            }
+            None => return Err(self.error("stack overflow (cant pop an empty stack)")),
+        };
+        let mut fields: Vec<Option<Value>> = vec![None; kind.fields.len()];
+        for (name, value) in names.into_iter().zip(values) {
+            match kind.fields.iter().position(|field| *field == name) {
+                Some(field) => fields[field] = Some(value),
+                None => return Err(self.no_field(&kind, &name)),
+            }
+        }
+        let mut instance = Instance {
+            kind: kind.clone(),
+            fields: Vec::with_capacity(fields.len()),
+        };
+        for (name, value) in kind.fields.iter().zip(fields) {
+            match value {
+                Some(x) => instance.fields.push(x),
+                None => {
+                    return Err(self
+                        .diagnostic(&format!("{} is missing the field {name}", kind.name))
+                        .help("every field needs a value when you make a struct")
+                        .into())
                 }
-            };
-            let mut vec: Vec<Value> = Vec::new();
-            for i in start as usize..end as usize {
-                vec.push(Value::Number(i as f64));
             }
-            self.push(Value::Vec(vec))
         }
+        self.push(Value::Struct(instance));
+        Ok(())
     }
-    fn for_loop(&mut self) {
-        let range = match self.pop() {
-            Some(x) => x,
-            None => {
-                self.error("invalid witewabwe!");
-                panic!();
+    fn implement(&mut self, names: Vec<String>) -> Result<(), KlangError> {
+        let mut functions: Vec<Function> = Vec::with_capacity(names.len());
+        for _ in 0..names.len() {
+            match self.pop() {
+                Some(Value::Function(function)) => functions.insert(0, function),
+                _ => return Err(self.error("ksang made a little oopsy")),
             }
+        }
+        match self.pop() {
+            Some(Value::Type(kind)) => {
+                //a second impl of the same struct adds to it, and replaces what it names again
+                kind.methods
+                    .borrow_mut()
+                    .extend(names.into_iter().zip(functions));
+                Ok(())
+            }
+            Some(x) => Err(self
+                .diagnostic(&format!("cant impl {x}"))
+                .note("only structs can have an impl")
+                .into()),
+            None => Err(self.error("stack overflow (cant pop an empty stack)")),
+        }
+    }
+    fn invoke(&mut self, name: String, arg_num: i32) -> Result<(), KlangError> {
+        //p.f(x) is Point::f(p, x) if f takes self, otherwise it calls the function in the field f
+        let mut args: Vec<Value> = Vec::new();
+        for _ in 0..arg_num {
+            match self.pop() {
+                Some(x) => args.insert(0, x),
+                None => return Err(self.error("not enough arguments!")),
+            }
+        }
+        let instance = match self.pop() {
+            Some(Value::Struct(instance)) => instance,
+            Some(x) => {
+                return Err(self
+                    .diagnostic(&format!("cant call the method {name} on {x}"))
+                    .note("only structs have methods")
+                    .into())
+            }
+            None => return Err(self.error("stack overflow (cant pop an empty stack)")),
         };
-        let mut vector = match range {
-            Value::Vec(x) => x,
-            _ => {
-                self.error("invalid witewabwe!");
-                panic!();
+        let method = instance.kind.methods.borrow().get(&name).cloned();
+        let mut arg_num = arg_num;
+        match method {
+            Some(method) => {
+                if method.params.first().map(String::as_str) != Some("self") {
+                    return Err(self
+                        .diagnostic(&format!("{method} is not a method, it doesnt take self"))
+                        .help(&format!("call it with {}::{name}(...)", instance.kind.name))
+                        .into());
+                }
+                if method.params.len() - 1 != args.len() {
+                    return Err(self.error(&format!(
+                        "{method} takes {} arguments but you gave it {arg_num}",
+                        method.params.len() - 1
+                    )));
+                }
+                self.push(Value::Function(method));
+                self.push(Value::Struct(instance));
+                arg_num += 1;
             }
+            None => match instance.field(&name) {
+                Some(field) => self.push(instance.fields[field].clone()),
+                None => return Err(self.no_method(&instance.kind, &name)),
+            },
+        }
+        for arg in args {
+            self.push(arg);
+        }
+        self.call(arg_num)
+    }
+    fn no_method(&self, kind: &StructType, name: &str) -> KlangError {
+        self.diagnostic(&format!("{} has no function called {name}", kind.name))
+            .help(&format!("add it in an impl {} {{ ... }}", kind.name))
+            .into()
+    }
+    fn instance(&mut self, field: &str) -> Result<Instance, KlangError> {
+        match self.pop() {
+            Some(Value::Struct(instance)) => Ok(instance),
+            Some(x) => Err(self
+                .diagnostic(&format!("cant get the field {field} of {x}"))
+                .note("only structs have fields")
+                .into()),
+            None => Err(self.error("stack overflow (cant pop an empty stack)")),
+        }
+    }
+    fn field(&self, instance: &Instance, name: &str) -> Result<usize, KlangError> {
+        instance
+            .field(name)
+            .ok_or_else(|| self.no_field(&instance.kind, name))
+    }
+    fn no_field(&self, kind: &StructType, name: &str) -> KlangError {
+        self.diagnostic(&format!("{} has no field called {name}", kind.name))
+            .note(&format!("its fields are: {}", kind.fields.join(", ")))
+            .into()
+    }
+    fn iterate(&self, value: Value) -> Result<Vec<Value>, KlangError> {
+        //everything a for loop can go over, turned into the values it goes through
+        match value {
+            Value::Vec(x) => Ok(x),
+            Value::Map(x) => Ok(x.into_keys().map(|key| key.value()).collect()),
+            Value::String { string, .. } => Ok(string
+                .chars()
+                .map(|ch| Value::String {
+                    string: ch.to_string(),
+                    printables: Vec::new(),
+                })
+                .collect()),
+            x => Err(self
+                .diagnostic(&format!("cant loop over {x}"))
+                .note("for loops go over ranges, vectors, strings and maps")
+                .into()),
+        }
+    }
+    fn entries(&self, value: Value) -> Result<Vec<Value>, KlangError> {
+        //for key, value in ... goes over [key, value] pairs, the key of a vector or a string is the index
+        Ok(match value {
+            Value::Map(x) => x
+                .into_iter()
+                .map(|(key, value)| Value::Vec(vec![key.value(), value]))
+                .collect(),
+            x => self
+                .iterate(x)?
+                .into_iter()
+                .enumerate()
+                .map(|(i, value)| Value::Vec(vec![Value::Number(i as f64), value]))
+                .collect(),
+        })
+    }
+    fn range(&mut self, cstep: bool) -> Result<(), KlangError> {
+        let step = if cstep { self.range_bound("step")? } else { 1 };
+        let end = self.range_bound("end")?;
+        let start = self.range_bound("start")?;
+        if step < 1 {
+            return Err(self
+                .diagnostic(&format!("range step must be at least 1 but its {step}"))
+                .help("ranges only count up, a range with its start after its end is empty")
+                .into());
+        }
+        let vec: Vec<Value> = (start..end)
+            .step_by(step as usize)
+            .map(|i| Value::Number(i as f64))
+            .collect();
+        self.push(Value::Vec(vec));
+        Ok(())
+    }
+    fn range_bound(&mut self, name: &str) -> Result<i64, KlangError> {
+        match self.pop() {
+            Some(Value::Number(x)) if x.fract() == 0.0 => Ok(x as i64),
+            Some(Value::Number(x)) => Err(self
+                .diagnostic(&format!("range {name} must be a whole number but its {x}"))
+                .help("round it first with std::round")
+                .into()),
+            Some(x) => Err(self.error(&format!("range {name} must be a number but its {x}"))),
+            None => Err(self.error("stack overflow (cant pop an empty stack)")),
+        }
+    }
+    fn for_loop(&mut self) -> Result<(), KlangError> {
+        let mut vector = match self.pop() {
+            Some(Value::Vec(x)) => x,
+            _ => return Err(self.error("invalid witewabwe!")),
         };
         self.index += 1;
+        let Some(value) = vector.pop() else {
+            //done, skip the rest of the loop (Scope Let JumpIf body Jump) without
+            //opening a scope for it, otherwise an outer loop would lose its own scope
+            if let OpCode::JumpIf(len, _) = self.chunk.code[self.index as usize + 2] {
+                return self.jump(2 + len);
+            }
+            return Err(self.error("ksang made a little oopsy"));
+        };
+        //whats left goes under the loops scope, For takes it back out next time around
+        self.push(Value::Vec(vector));
         self.create_inner();
-        if vector.is_empty() {
-            self.push(Value::Bool(true));
-            self.push(Value::None);
-            return;
-        } else {
-            self.push(Value::Bool(false));
-            self.push(vector.remove(0));
-        }
-        let mut scope: &mut Scope = &mut self.global;
-        while scope.inner.as_mut().unwrap().inner.is_some() {
-            scope = scope.inner.as_mut().unwrap();
-        }
-        scope.stack.push(Value::Vec(vector));
-    }
-    fn print(&mut self) {
-        let mut print = match self.pop() {
+        self.push(Value::Bool(false));
+        self.push(value);
+        Ok(())
+    }
+    fn print(&mut self) -> Result<(), KlangError> {
+        match self.pop() {
+            Some(Value::String {
+                string,
+                printables: _,
+            }) => println!("{string}"),
+            _ => return Err(self.error("can only print strings")),
+        };
+        Ok(())
+    }
+    fn format(&mut self, holes: i32) -> Result<(), KlangError> {
+        let template = match self.pop() {
             Some(Value::String {
                 string,
                 printables: _,
             }) => string,
-            _ => {
-                panic!()
-            }
+            _ => return Err(self.error("ksang made a little oopsy")),
         };
-        for _ in 0..self.count_braces(print.as_str()) {
+        let mut values: Vec<String> = Vec::new();
+        for _ in 0..holes {
             let repl = match self.pop() {
                 Some(Value::String {
                     string,
@@ -291,165 +628,100 @@ impl<'a> VM<'a> {
                 }) => string,
                 Some(Value::Number(x)) => x.to_string(),
                 Some(Value::Bool(x)) => x.to_string(),
-                Some(Value::Vec(x)) => format!("{:?}", x),
-                Some(Value::None) => "None".to_string(),
-                None => {
-                    self.error("Stack overflow (cant pop an empty stack)");
-                    panic!()
+                Some(x @ (Value::Vec(_) | Value::Map(_) | Value::Type(_) | Value::Struct(_))) => {
+                    x.to_string()
                 }
+                Some(Value::Function(x)) => x.to_string(),
+                Some(Value::None) => "None".to_string(),
+                None => return Err(self.error("Stack overflow (cant pop an empty stack)")),
             };
-            print = self.replace_last_braces(print.as_str(), repl.as_str());
-        }
-        println!("{}", print);
-    }
-    fn count_braces(&self, string: &str) -> usize {
-        let mut count = 0;
-        let mut braces = 0;
-        for c in string.chars() {
-            match c {
-                '{' => braces += 1,
-                '}' => {
-                    if braces > 0 {
-                        braces -= 1;
-                        if braces == 0 {
-                            count += 1;
-                        }
-                    }
-                }
-                _ => {}
-            }
-        }
-        count
-    }
-    fn replace_last_braces(&self, string: &str, replacement: &str) -> String {
-        if let Some((start, _)) = string.rmatch_indices("{}").next() {
-            let mut modified = String::with_capacity(string.len() - 2 + replacement.len());
-            modified.push_str(&string[..start]);
-            modified.push_str(replacement);
-            modified.push_str(&string[start + 2..]);
-            modified
-        } else {
-            String::from(string)
-        }
-    }
-    fn get_var(name: &str, scope: &mut Scope) -> (Option<Value>, bool) {
-        //gets a variable from the most inner scope, if its not there searches on the outer scopes, return true when found the variable
-        if scope.inner.is_some() {
-            let i = VM::get_var(name, scope.inner.as_mut().unwrap());
-            if !i.1 {
-                return match scope.callframe.get(name) {
-                    Some(val) => (Some(val.clone()), true),
-                    None => (None, false),
-                };
-            }
-            return i;
-        }
-        match scope.callframe.get(name) {
-            Some(val) => (Some(val.clone()), true),
-            None => (None, false),
+            values.push(repl);
         }
+        values.reverse(); //the last printable is on top of the stack
+        self.push(Value::String {
+            string: fill_holes(&template, &values),
+            printables: Vec::new(),
+        });
+        Ok(())
+    }
+    fn get_var(&self, name: &str) -> Option<Value> {
+        //gets a variable from the most inner scope, if its not there searches on the outer scopes
+        self.scopes.iter().rev().find_map(|scope| {
+            let slot = scope.callframe.borrow().get(name).cloned();
+            slot.and_then(|slot| slot.borrow().clone())
+        })
     }
     fn set_var(&mut self, name: String) {
-        //sets a variable in the most inner scope, to the top value of the stack
+        //changes the variable in the outermost scope that has it, or makes it in the most inner one
         let pop = match self.pop() {
             Some(x) => x,
             None => Value::None,
         };
-        let mut scope: &mut Scope = &mut self.global;
-        while scope.inner.is_some() {
-            if let std::collections::hash_map::Entry::Occupied(mut e) =
-                scope.callframe.entry(name.clone())
-            {
-                e.insert(pop);
-                return;
+        let slot = self
+            .scopes
+            .iter()
+            .find_map(|scope| scope.callframe.borrow().get(&name).cloned());
+        match slot {
+            Some(slot) => *slot.borrow_mut() = Some(pop),
+            None => {
+                let scope = self.scopes.last().unwrap();
+                scope
+                    .callframe
+                    .borrow_mut()
+                    .insert(name, Rc::new(RefCell::new(Some(pop))));
             }
-            scope = scope.inner.as_mut().unwrap();
-        }
-        scope.callframe.insert(name, pop);
-    }
-    fn set_var_inner(&mut self, name: String) {
-        //sets a variable in the most inner scope, to the top value of the stack
-        let pop = match self.pop() {
-            Some(x) => x,
-            None => Value::None,
-        };
-        let mut scope: &mut Scope = &mut self.global;
-        while scope.inner.is_some() {
-            scope = scope.inner.as_mut().unwrap();
         }
-        scope.callframe.insert(name, pop);
     }
     fn create_inner(&mut self) {
-        let mut scope: &mut Scope = &mut self.global;
-        while scope.inner.is_some() {
-            scope = scope.inner.as_mut().unwrap();
-        }
-        scope.inner = Some(Box::new(Scope::new()));
+        self.scopes.push(Scope::new());
     }
     fn close_inner(&mut self) {
-        let mut scope: &mut Scope = &mut self.global;
-        while scope.inner.as_mut().unwrap().inner.is_some() {
-            scope = scope.inner.as_mut().unwrap();
-        }
-        scope.inner = None;
+        self.scopes.pop();
     }
-    fn error(&self, msg: &str) {
-        KlangError::error(
-            KlangError::RuntimeError,
+    fn error(&self, msg: &str) -> KlangError {
+        self.diagnostic(msg).into()
+    }
+    fn diagnostic(&self, msg: &str) -> Diagnostic {
+        Diagnostic::new(
+            ErrorKind::RuntimeError,
             msg,
-            self.chunk.lines[self.index as usize],
-            self.filename,
-        );
+            self.chunk.spans[self.index as usize],
+        )
     }
 
-    fn bin_op(&mut self, operation: TokenType) {
-        let pop2 = self.pop2();
+    fn bin_op(&mut self, operation: TokenType) -> Result<(), KlangError> {
+        let pop2 = self.pop2()?;
         self.push(match operation {
             TokenType::Plus => match pop2 {
                 (Value::Number(x), Value::Number(y)) => Value::Number(x + y),
-                _ => {
-                    self.error("can only add numbers");
-                    panic!()
-                }
+                _ => return Err(self.error("can only add numbers")),
             },
             TokenType::Minus => match pop2 {
                 (Value::Number(x), Value::Number(y)) => Value::Number(y - x),
-                _ => {
-                    self.error("can only subtract numbers");
-                    panic!()
-                }
+                _ => return Err(self.error("can only subtract numbers")),
             },
             TokenType::Star => match pop2 {
                 (Value::Number(x), Value::Number(y)) => Value::Number(x * y),
-                _ => {
-                    self.error("can only multiply numbers");
-                    panic!()
-                }
+                _ => return Err(self.error("can only multiply numbers")),
             },
             TokenType::Slash => match pop2 {
                 (Value::Number(x), Value::Number(y)) => {
                     if x == 0.0 {
-                        self.error("division by zero");
-                        panic!()
+                        return Err(self.error("division by zero"));
                     }
                     Value::Number(y / x)
                 }
-                _ => {
-                    self.error("can only divide numbers");
-                    panic!()
-                }
+                _ => return Err(self.error("can only divide numbers")),
             },
             TokenType::Modulo => match pop2 {
                 (Value::Number(x), Value::Number(y)) => {
                     if x == 0.0 {
-                        self.error("no modulo by zero");
-                        panic!()
+                        return Err(self.error("no modulo by zero"));
                     }
                     Value::Number(y % x)
                 }
                 _ => {
-                    self.error("can only use the modulo operator on numbers, dickfuck");
-                    panic!()
+                    return Err(self.error("can only use the modulo operator on numbers, dickfuck"))
                 }
             },
             TokenType::EqualEqual => match pop2 {
@@ -470,126 +742,128 @@ impl<'a> VM<'a> {
             },
             TokenType::Less => match pop2 {
                 (Value::Number(x), Value::Number(y)) => Value::Bool(x > y),
-                _ => {
-                    self.error("can only compare numbers");
-                    panic!()
-                }
+                _ => return Err(self.error("can only compare numbers")),
             },
             TokenType::LessEqual => match pop2 {
                 (Value::Number(x), Value::Number(y)) => Value::Bool(x >= y),
-                _ => {
-                    self.error("can only compare numbers");
-                    panic!()
-                }
+                _ => return Err(self.error("can only compare numbers")),
             },
             TokenType::Greater => match pop2 {
                 (Value::Number(x), Value::Number(y)) => Value::Bool(x < y),
-                _ => {
-                    self.error("can only compare numbers");
-                    panic!()
-                }
+                _ => return Err(self.error("can only compare numbers")),
             },
             TokenType::GreaterEqual => match pop2 {
                 (Value::Number(x), Value::Number(y)) => Value::Bool(x <= y),
-                _ => {
-                    self.error("can only compare numbers");
-                    panic!()
-                }
+                _ => return Err(self.error("can only compare numbers")),
             },
             TokenType::And => match pop2 {
                 (Value::Bool(x), Value::Bool(y)) => Value::Bool(x && y),
-                _ => {
-                    self.error("can only perform logical AND on bool values");
-                    panic!()
-                }
+                _ => return Err(self.error("can only perform logical AND on bool values")),
             },
             TokenType::Or => match pop2 {
                 (Value::Bool(x), Value::Bool(y)) => Value::Bool(x || y),
-                _ => {
-                    self.error("can only perform logical OR on bool values");
-                    panic!()
-                }
+                _ => return Err(self.error("can only perform logical OR on bool values")),
             },
-            _ => {
-                self.error("unsupported binary operation");
-                panic!()
-            }
+            _ => return Err(self.error("unsupported binary operation")),
         });
+        Ok(())
     }
-    fn un_op(&mut self, operation: TokenType) {
+    fn un_op(&mut self, operation: TokenType) -> Result<(), KlangError> {
         let pop = match self.pop() {
             Some(x) => x,
-            None => {
-                self.error("stack overflow (cant pop an empty stack)");
-                panic!()
-            }
+            None => return Err(self.error("stack overflow (cant pop an empty stack)")),
         };
         self.push(match operation {
             TokenType::Bang => match pop {
                 Value::Bool(x) => Value::Bool(!x),
-                _ => {
-                    self.error("can only use ! on bools");
-                    panic!()
-                }
+                _ => return Err(self.error("can only use ! on bools")),
             },
             TokenType::Minus => match pop {
                 Value::Number(x) => Value::Number(-x),
-                _ => {
-                    self.error("can only use minus on ints and floats");
-                    panic!()
-                }
+                _ => return Err(self.error("can only use minus on ints and floats")),
             },
-            _ => {
-                self.error("unsupported unary operation");
-                panic!()
-            }
-        })
+            _ => return Err(self.error("unsupported unary operation")),
+        });
+        Ok(())
     }
-    fn call(&mut self, callee: String, index: i32) {
-        let fun = match self.functions.remove(&callee) {
-            Some(x) => x,
-            None => {
-                self.error("please call a real function next time stupid ass mf");
-                panic!()
+    fn call(&mut self, arg_num: i32) -> Result<(), KlangError> {
+        let mut args: Vec<Value> = Vec::new();
+        for _ in 0..arg_num {
+            match self.pop() {
+                Some(x) => args.insert(0, x),
+                None => return Err(self.error("not enough arguments!")),
             }
-        };
-        self.functions.insert(callee.clone(), fun.clone());
-        self.create_inner();
-        for i in fun.1.into_iter().rev() {
-            let mut scope: &mut Scope = &mut self.global;
-            while scope.inner.as_mut().unwrap().inner.is_some() {
-                scope = scope.inner.as_mut().unwrap();
+        }
+        let function = match self.pop() {
+            Some(Value::Function(function)) => function,
+            Some(x) => {
+                return Err(self
+                    .diagnostic("please call a real function next time stupid ass mf")
+                    .note(format!("\"{x}\" is not a function").as_str())
+                    .into())
             }
-            let pop = match scope.stack.pop() {
-                Some(x) => x,
-                None => {
-                    self.error("not enough arguments!");
-                    panic!()
-                }
-            };
-            self.push(pop);
-            self.set_var_inner(i);
+            None => return Err(self.error("stack overflow (cant pop an empty stack)")),
+        };
+        if function.params.len() != args.len() {
+            return Err(self.error(
+                format!(
+                    "{function} takes {} arguments but you gave it {arg_num}",
+                    function.params.len()
+                )
+                .as_str(),
+            ));
+        }
+        if self.frames.len() == MAX_FRAMES {
+            return Err(self
+                .diagnostic("stack overflow (too many calls inside each other)")
+                .help("make sure your recursion stops somewhere")
+                .into());
+        }
+        //the function runs on the globals, with what it captured and its arguments on top
+        let mut scopes = vec![Scope {
+            callframe: self.scopes[0].callframe.clone(),
+            stack: Vec::new(),
+        }];
+        let captured = Scope::new();
+        captured
+            .callframe
+            .borrow_mut()
+            .extend(function.captured.iter().cloned());
+        scopes.push(captured);
+        let arguments = Scope::new();
+        if let Some(name) = &function.name {
+            //it can always call itself, without capturing the variable its stored in
+            arguments.callframe.borrow_mut().insert(
+                name.clone(),
+                Rc::new(RefCell::new(Some(Value::Function(function.clone())))),
+            );
         }
-        let mut b = 0;
-        for (i, op) in fun.0.into_iter().enumerate() {
-            self.chunk.code.insert(index as usize + i + 1, op);
-            self.chunk.lines.push(0);
-            b = index as usize + i + 2;
+        for (param, arg) in function.params.into_iter().zip(args) {
+            arguments
+                .callframe
+                .borrow_mut()
+                .insert(param, Rc::new(RefCell::new(Some(arg))));
         }
-        self.chunk.code.insert(b, OpCode::EndFn);
+        scopes.push(arguments);
+        self.frames.push(Frame {
+            scopes: std::mem::replace(&mut self.scopes, scopes),
+            index: self.index,
+        });
+        self.index = function.start as i32 - 1; //run moves on to the start
+        Ok(())
     }
-    fn native_call(&mut self, callee: String, arg_num: i32) {
+    fn native_call(&mut self, callee: String, arg_num: i32) -> Result<(), KlangError> {
         let mut found = false;
         for i in 0..self.native.len() {
             if self.native[i].name == callee {
                 if arg_num != self.native[i].args {
-                    self.error(
+                    return Err(self.error(
                         format!(
                             "the function takes {} arguments but you only gave it {arg_num}",
                             self.native[i].args
                         )
                         .as_str(),
-                    )
+                    ));
                 }
                 let mut args: Vec<Value> = Vec::new();
                 for _ in 0..arg_num {
@@ -597,84 +871,70 @@ impl<'a> VM<'a> {
                         0,
                         match self.pop() {
                             Some(x) => x,
-                            None => {
-                                self.error("not enough arguments!");
-                                panic!()
-                            }
+                            None => return Err(self.error("not enough arguments!")),
                         },
                     )
                 }
-                if let Some(x) = self.native[i].call(args) {
-                    self.push(x)
+                match self.native[i].call(args) {
+                    Ok(Some(x)) => self.push(x),
+                    Ok(None) => {}
+                    Err(msg) => return Err(self.error(&msg)),
                 }
                 found = true;
                 break;
             }
         }
         if !found {
-            self.error("not a real native function dumbass");
+            return Err(self
+                .diagnostic("not a real native function dumbass")
+                .note(format!("there is no native function named \"{callee}\"").as_str())
+                .into());
         }
+        Ok(())
     }
 
-    fn pop2(&mut self) -> (Value, Value) {
-        (
+    fn pop2(&mut self) -> Result<(Value, Value), KlangError> {
+        Ok((
             match self.pop() {
                 Some(x) => x,
-                None => {
-                    self.error("stack overflow (cant pop an empty stack)");
-                    panic!()
-                }
+                None => return Err(self.error("stack overflow (cant pop an empty stack)")),
             },
             match self.pop() {
                 Some(x) => x,
-                None => {
-                    self.error("stack overflow (cant pop an empty stack)");
-                    panic!()
-                }
+                None => return Err(self.error("stack overflow (cant pop an empty stack)")),
             },
-        )
+        ))
     }
     fn pop(&mut self) -> Option<Value> {
-        let mut scope: &mut Scope = &mut self.global;
-        while scope.inner.is_some() {
-            scope = scope.inner.as_mut().unwrap();
-        }
-        scope.stack.pop()
+        self.scopes.last_mut().unwrap().stack.pop()
     }
-    fn top(&mut self) -> Value {
-        let mut scope: &mut Scope = &mut self.global;
-        while scope.inner.is_some() {
-            scope = scope.inner.as_mut().unwrap();
-        }
-        if scope.stack.is_empty() {
-            self.error("stack overflow (cant top an empty stack)");
-            panic!()
+    fn top(&mut self) -> Result<Value, KlangError> {
+        match self.scopes.last().unwrap().stack.last() {
+            Some(val) => Ok(val.clone()),
+            None => Err(self.error("stack overflow (cant top an empty stack)")),
         }
-        let val = scope.stack.pop().unwrap();
-        scope.stack.push(val.clone());
-        val
     }
     fn push(&mut self, v: Value) {
-        let mut scope: &mut Scope = &mut self.global;
-        while scope.inner.is_some() {
-            scope = scope.inner.as_mut().unwrap();
-        }
-        scope.stack.push(v);
+        self.scopes.last_mut().unwrap().stack.push(v);
     }
 }
 
 #[derive(Debug, Clone)]
 pub struct Scope {
-    pub callframe: HashMap<String, Value>,
-    pub inner: Option<Box<Scope>>,
+    pub callframe: Env,
     pub stack: Vec<Value>,
 }
 impl Scope {
     pub fn new() -> Self {
         Self {
-            callframe: HashMap::new(),
-            inner: None,
+            callframe: Rc::new(RefCell::new(HashMap::new())),
             stack: Vec::new(),
         }
     }
 }
+
+//a caller waiting for its call to return
+pub struct Frame {
+    pub scopes: Vec<Scope>,
+    pub index: i32, //where the call is
+}
@@ -0,0 +1,172 @@
+use crate::error::ErrorKind;
+use crate::interpreter::scanner::Span;
+use colored::*;
+
+//spans longer than this are shown with their middle lines cut out
+const MAX_LINES: usize = 4;
+
+//how diagnostics are written to stderr, chosen with --error-format=human|json
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ErrorFormat {
+    Human,
+    Json,
+}
+
+impl ErrorFormat {
+    pub fn parse(format: &str) -> Option<ErrorFormat> {
+        match format {
+            "human" => Some(ErrorFormat::Human),
+            "json" => Some(ErrorFormat::Json),
+            _ => None,
+        }
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct Diagnostic {
+    pub kind: ErrorKind,
+    pub message: String,
+    pub span: Span,
+    pub notes: Vec<String>,
+    pub help: Option<String>,
+}
+
+impl Diagnostic {
+    pub fn new(kind: ErrorKind, message: &str, span: Span) -> Diagnostic {
+        Diagnostic {
+            kind,
+            message: message.to_string(),
+            span,
+            notes: Vec::new(),
+            help: None,
+        }
+    }
+    pub fn note(mut self, note: &str) -> Diagnostic {
+        self.notes.push(note.to_string());
+        self
+    }
+    pub fn help(mut self, help: &str) -> Diagnostic {
+        self.help = Some(help.to_string());
+        self
+    }
+
+    //renders the diagnostic like rustc does:
+    //[ParserError]: missing ; at the end of the line
+    //  --> main.klang:3:10
+    //   |
+    // 3 | let x = 5
+    //   |          ^
+    //   = help: ...
+    pub fn render(&self, source: &str, filename: &str) -> String {
+        let mut out = format!(
+            "{} {}",
+            format!("[{:?}]:", self.kind).red().bold(),
+            self.message.bold()
+        );
+        let lines: Vec<&str> = source.lines().collect();
+        let has_snippet = self.span.line > 0 && self.span.line <= lines.len();
+        let width = if has_snippet {
+            self.span.end_line.max(self.span.line).to_string().len()
+        } else {
+            1
+        };
+        let gutter = format!("{} |", " ".repeat(width)).blue().bold();
+        if has_snippet {
+            out += &format!(
+                "\n{}{} {filename}:{}:{}",
+                " ".repeat(width),
+                "-->".blue().bold(),
+                self.span.line,
+                self.span.column
+            );
+            out += &format!("\n{gutter}");
+            let last = self.span.end_line.clamp(self.span.line, lines.len());
+            for line in self.span.line..=last {
+                if last - self.span.line >= MAX_LINES && line > self.span.line + 1 && line < last {
+                    if line == self.span.line + 2 {
+                        out += &format!("\n{}", "...".blue().bold());
+                    }
+                    continue;
+                }
+                let text = lines[line - 1];
+                let start = if line == self.span.line {
+                    self.span.column
+                } else {
+                    text.chars().take_while(|c| c.is_whitespace()).count() + 1
+                };
+                let end = if line == self.span.end_line {
+                    self.span.end_column
+                } else {
+                    text.chars().count() + 1
+                };
+                out += &format!("\n{} {text}", format!("{line:>width$} |").blue().bold());
+                out += &format!("\n{gutter} {}", underline(text, start, end));
+            }
+        } else if !filename.is_empty() {
+            out += &format!("\n{} {filename}", "-->".blue().bold());
+        }
+        for note in &self.notes {
+            out += &format!("\n{}{} {note}", " ".repeat(width + 1), "= note:".bold());
+        }
+        if let Some(help) = &self.help {
+            out += &format!("\n{}{} {help}", " ".repeat(width + 1), "= help:".bold());
+        }
+        out
+    }
+}
+
+impl Diagnostic {
+    //renders the diagnostic as a single line json object, for editors and ci tools
+    pub fn to_json(&self, filename: &str) -> String {
+        let notes: Vec<String> = self.notes.iter().map(|note| json_string(note)).collect();
+        format!(
+            "{{\"kind\":{},\"message\":{},\"file\":{},\"line\":{},\"column\":{},\"span\":{{\"start\":{},\"end\":{},\"line\":{},\"column\":{},\"end_line\":{},\"end_column\":{}}},\"notes\":[{}],\"help\":{}}}",
+            json_string(&format!("{:?}", self.kind)),
+            json_string(&self.message),
+            json_string(filename),
+            self.span.line,
+            self.span.column,
+            self.span.start,
+            self.span.end,
+            self.span.line,
+            self.span.column,
+            self.span.end_line,
+            self.span.end_column,
+            notes.join(","),
+            match &self.help {
+                Some(help) => json_string(help),
+                None => "null".to_string(),
+            }
+        )
+    }
+}
+
+fn json_string(string: &str) -> String {
+    let mut out = String::with_capacity(string.len() + 2);
+    out.push('"');
+    for c in string.chars() {
+        match c {
+            '"' => out.push_str("\\\""),
+            '\\' => out.push_str("\\\\"),
+            '\n' => out.push_str("\\n"),
+            '\r' => out.push_str("\\r"),
+            '\t' => out.push_str("\\t"),
+            c if (c as u32) < 0x20 => out.push_str(&format!("\\u{:04x}", c as u32)),
+            c => out.push(c),
+        }
+    }
+    out.push('"');
+    out
+}
+
+//carets under the columns start..end of text (columns start from 1, end is exclusive).
+//tabs are copied into the padding so the carets line up with the source line
+fn underline(text: &str, start: usize, end: usize) -> String {
+    let padding: String = text
+        .chars()
+        .take(start.saturating_sub(1))
+        .map(|c| if c == '\t' { '\t' } else { ' ' })
+        .collect();
+    let carets = "^".repeat(end.saturating_sub(start).max(1));
+    format!("{padding}{}", carets.red().bold())
+}
@@ -1,24 +1,59 @@
 #![allow(clippy::enum_variant_names)]
-use colored::*;
-#[derive(Debug)]
-pub enum KlangError {
+use crate::diagnostic::{Diagnostic, ErrorFormat};
+use std::fmt;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ErrorKind {
     ScannerError,
     ParserError,
+    CompilerError,
     RuntimeError,
 }
 
+//everything that went wrong while running a klang file. the scanner and parser
+//can find more than one problem at once, so an error holds a list of diagnostics
+#[derive(Debug, Clone)]
+pub struct KlangError {
+    pub diagnostics: Vec<Diagnostic>,
+}
+
 impl KlangError {
-    pub fn error(et: KlangError, msg: &str, line: usize, filename: &str) {
-        eprintln!(
-            "{} {} {}",
-            format!("[{et:?}]").red(),
-            filename.to_string().blue().bold(),
-            format!("at line {line}: {msg}").red()
-        );
-        match et {
-            Self::ScannerError => (),
-            Self::ParserError => std::process::exit(0),
-            Self::RuntimeError => std::process::exit(0),
+    pub fn new(diagnostics: Vec<Diagnostic>) -> KlangError {
+        KlangError { diagnostics }
+    }
+    pub fn report(&self, source: &str, filename: &str, format: ErrorFormat) {
+        for (i, diagnostic) in self.diagnostics.iter().enumerate() {
+            match format {
+                ErrorFormat::Human if i > 0 => {
+                    eprintln!("\n{}", diagnostic.render(source, filename))
+                }
+                ErrorFormat::Human => eprintln!("{}", diagnostic.render(source, filename)),
+                ErrorFormat::Json => eprintln!("{}", diagnostic.to_json(filename)),
+            }
+        }
+    }
+}
+
+impl From<Diagnostic> for KlangError {
+    fn from(diagnostic: Diagnostic) -> Self {
+        KlangError::new(vec![diagnostic])
+    }
+}
+
+impl fmt::Display for KlangError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        for (i, diagnostic) in self.diagnostics.iter().enumerate() {
+            if i > 0 {
+                writeln!(f)?;
+            }
+            write!(
+                f,
+                "[{:?}] at {}: {}",
+                diagnostic.kind, diagnostic.span, diagnostic.message
+            )?;
         }
+        Ok(())
     }
 }
+
+impl std::error::Error for KlangError {}
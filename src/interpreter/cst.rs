@@ -0,0 +1,243 @@
+use super::scanner::{Scanner, Span, TokenType};
+use crate::diagnostic::Diagnostic;
+use std::fmt;
+
+//the concrete syntax tree keeps every byte of the file, whitespace and comments included,
+//so printing it gives back the exact source even when the code has errors in it.
+//its only statements, blocks and brackets. whats between them stays a flat list of tokens,
+//the real parser is the one that understands expressions
+//
+//limits, until the parser builds this tree itself while it consumes tokens:
+//- the Builder below is its own small parser, not parser.rs. it guesses where statements
+//  and blocks end from keywords, ; and brackets, and it can drift from what the parser does.
+//  for example `for k in {"a": 1} { }` ends at the map for it, because a statement that
+//  ends with a block stops at its first {, so the real body becomes a separate BlockStmt
+//- there are no expression nodes (no binary, call or index), so a formatter or an editor
+//  cant ask it what an operand or an argument is, only where the brackets are
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SyntaxKind {
+    Root,
+    LetDecl,
+    FnDecl,
+    StructDecl,
+    Impl,
+    If,
+    While,
+    For,
+    Return,
+    Break,
+    Continue,
+    Print,
+    BlockStmt,
+    ExprStmt,
+    Block, // { ... }
+    Group, // ( ... )
+    List,  // [ ... ]
+}
+
+#[derive(Debug, Clone)]
+pub struct SyntaxToken {
+    pub tt: TokenType,
+    pub text: String, //exactly what is written in the source
+    #[allow(dead_code)] // printing the tree back only needs the text, the rest is for tools
+    pub span: Span,
+}
+
+#[derive(Debug, Clone)]
+pub enum SyntaxElement {
+    Node(SyntaxNode),
+    Token(SyntaxToken),
+}
+
+#[derive(Debug, Clone)]
+pub struct SyntaxNode {
+    #[allow(dead_code)]
+    pub kind: SyntaxKind,
+    pub children: Vec<SyntaxElement>,
+}
+
+impl fmt::Display for SyntaxNode {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        for child in &self.children {
+            match child {
+                SyntaxElement::Node(node) => write!(f, "{node}")?,
+                SyntaxElement::Token(token) => write!(f, "{}", token.text)?,
+            }
+        }
+        Ok(())
+    }
+}
+
+//builds the tree of a whole file, along with the scanner errors
+pub fn parse(source: &str) -> (SyntaxNode, Vec<Diagnostic>) {
+    let mut scanner = Scanner::lossless(source);
+    let (tokens, diagnostics) = scanner.scan_tokens();
+    let tokens = tokens
+        .into_iter()
+        .filter(|token| token.tt != TokenType::Eof)
+        .map(|token| SyntaxToken {
+            tt: token.tt,
+            text: source[token.span.start..token.span.end].to_string(),
+            span: token.span,
+        })
+        .collect();
+    let mut builder = Builder { tokens, current: 0 };
+    let children = builder.items(None);
+    (
+        SyntaxNode {
+            kind: SyntaxKind::Root,
+            children,
+        },
+        diagnostics,
+    )
+}
+
+struct Builder {
+    tokens: Vec<SyntaxToken>,
+    current: usize,
+}
+
+impl Builder {
+    fn items(&mut self, closing: Option<TokenType>) -> Vec<SyntaxElement> {
+        //statements until the closing bracket (or the end of the file)
+        let mut children = Vec::new();
+        while let Some(tt) = self.peek() {
+            if Some(tt) == closing {
+                break;
+            }
+            if matches!(
+                tt,
+                TokenType::RightBrace | TokenType::RightParen | TokenType::RightSquare
+            ) {
+                //a closing bracket nobody opened, keep it as it is
+                self.trivia(&mut children);
+                children.push(SyntaxElement::Token(self.bump()));
+                continue;
+            }
+            children.push(SyntaxElement::Node(self.statement()));
+        }
+        self.trivia(&mut children);
+        children
+    }
+    fn statement(&mut self) -> SyntaxNode {
+        let mut children = Vec::new();
+        while self.peek() == Some(TokenType::DocComment) {
+            self.element(&mut children);
+        }
+        //a labeled loop is still a loop, look past the 'label:
+        let next = if self.peek() == Some(TokenType::Label) {
+            self.peek_nth(2)
+        } else {
+            self.peek()
+        };
+        let kind = match next {
+            Some(TokenType::Let) => SyntaxKind::LetDecl,
+            //fn( is a lambda, the statement is just an expression
+            Some(TokenType::Fn) if self.peek_nth(1) == Some(TokenType::Identifier) => {
+                SyntaxKind::FnDecl
+            }
+            Some(TokenType::If) => SyntaxKind::If,
+            Some(TokenType::While) => SyntaxKind::While,
+            Some(TokenType::For) => SyntaxKind::For,
+            Some(TokenType::Struct) => SyntaxKind::StructDecl,
+            Some(TokenType::Impl) => SyntaxKind::Impl,
+            Some(TokenType::Return) => SyntaxKind::Return,
+            Some(TokenType::Break) => SyntaxKind::Break,
+            Some(TokenType::Continue) => SyntaxKind::Continue,
+            Some(TokenType::Print) => SyntaxKind::Print,
+            Some(TokenType::LeftBrace) => SyntaxKind::BlockStmt,
+            _ => SyntaxKind::ExprStmt,
+        };
+        let ends_with_block = matches!(
+            kind,
+            SyntaxKind::FnDecl
+                | SyntaxKind::StructDecl
+                | SyntaxKind::Impl
+                | SyntaxKind::If
+                | SyntaxKind::While
+                | SyntaxKind::For
+                | SyntaxKind::BlockStmt
+        );
+        while let Some(tt) = self.peek() {
+            if matches!(
+                tt,
+                TokenType::RightBrace | TokenType::RightParen | TokenType::RightSquare
+            ) {
+                break; //belongs to whoever opened it
+            }
+            let tt = self.element(&mut children);
+            if tt == TokenType::Semicolon
+                || (tt == TokenType::LeftBrace
+                    && ends_with_block
+                    && self.peek() != Some(TokenType::Else))
+            {
+                break;
+            }
+        }
+        SyntaxNode { kind, children }
+    }
+    fn element(&mut self, children: &mut Vec<SyntaxElement>) -> TokenType {
+        //one token, or a whole bracket with everything inside of it
+        self.trivia(children);
+        let token = self.bump();
+        let tt = token.tt;
+        let (kind, closing) = match tt {
+            TokenType::LeftBrace => (SyntaxKind::Block, TokenType::RightBrace),
+            TokenType::LeftParen => (SyntaxKind::Group, TokenType::RightParen),
+            TokenType::LeftSquare => (SyntaxKind::List, TokenType::RightSquare),
+            _ => {
+                children.push(SyntaxElement::Token(token));
+                return tt;
+            }
+        };
+        let mut inner = vec![SyntaxElement::Token(token)];
+        if kind == SyntaxKind::Block {
+            inner.extend(self.items(Some(closing)));
+        } else {
+            while let Some(next) = self.peek() {
+                if matches!(
+                    next,
+                    TokenType::RightBrace | TokenType::RightParen | TokenType::RightSquare
+                ) {
+                    break;
+                }
+                self.element(&mut inner);
+            }
+            self.trivia(&mut inner);
+        }
+        if self.peek() == Some(closing) {
+            inner.push(SyntaxElement::Token(self.bump()));
+        }
+        children.push(SyntaxElement::Node(SyntaxNode {
+            kind,
+            children: inner,
+        }));
+        tt
+    }
+    fn trivia(&mut self, children: &mut Vec<SyntaxElement>) {
+        while self.current < self.tokens.len()
+            && matches!(
+                self.tokens[self.current].tt,
+                TokenType::Whitespace | TokenType::Comment
+            )
+        {
+            children.push(SyntaxElement::Token(self.bump()));
+        }
+    }
+    fn peek(&self) -> Option<TokenType> {
+        //the next token that isnt whitespace or a comment
+        self.peek_nth(0)
+    }
+    fn peek_nth(&self, n: usize) -> Option<TokenType> {
+        self.tokens[self.current..]
+            .iter()
+            .map(|token| token.tt)
+            .filter(|tt| !matches!(tt, TokenType::Whitespace | TokenType::Comment))
+            .nth(n)
+    }
+    fn bump(&mut self) -> SyntaxToken {
+        self.current += 1;
+        self.tokens[self.current - 1].clone()
+    }
+}
@@ -1,34 +1,103 @@
-use crate::scanner::{Token, Value};
+use super::stmt::Stmt;
+use crate::scanner::{Span, Token, Value};
 
 #[derive(Clone, Debug)]
 pub enum Expr {
     Assign {
         name: Token,
         value: Box<Expr>,
+        operator: Option<Token>, // the + of +=
+        span: Span,
     }, //assignment
     Binary {
         left: Box<Expr>,
         operator: Token,
         right: Box<Expr>,
+        span: Span,
     }, //binary operations (+, -, .., ==, !=, <=, ..)
     Call {
         callee: Box<Expr>,
         arguments: Vec<Expr>,
         native: bool,
+        span: Span,
     },
-    Grouping(Box<Expr>), // "(" expression ")"
-    Literal(Value, usize),
+    Grouping(Box<Expr>, Span), // "(" expression ")"
+    Literal(Value, Span),
 
     Unary {
         operator: Token,
         expression: Box<Expr>,
+        span: Span,
     }, // ! or - (negate)
     Variable(Token),
     Range {
         min: Box<Expr>,
         max: Box<Expr>,
         step: Option<Box<Expr>>,
-        line: usize,
+        span: Span,
     }, // range
-    Vec(Vec<Expr>),
+    Vec(Vec<Expr>, Span),
+    Map(Vec<(Expr, Expr)>, Span), // { key: value, ... }
+    Index {
+        object: Box<Expr>,
+        index: Box<Expr>,
+        span: Span,
+    }, // v[i]
+    Get {
+        object: Box<Expr>,
+        name: Token,
+        span: Span,
+    }, // p.x
+    Path {
+        object: Box<Expr>,
+        name: Token,
+        span: Span,
+    }, // Point::new
+    Set {
+        target: Box<Expr>,
+        value: Box<Expr>,
+        operator: Option<Token>,
+        span: Span,
+    }, // v[i] = x or p.x = x, target is an Index or a Get on a variable like m[i].x[j] = x
+    Struct {
+        name: Token,
+        fields: Vec<(Token, Expr)>,
+        span: Span,
+    }, // Point { x: 1, y: 2 }
+    Slice {
+        object: Box<Expr>,
+        start: Option<Box<Expr>>,
+        end: Option<Box<Expr>>,
+        step: Option<Box<Expr>>,
+        span: Span,
+    }, // v[a..b..s], every part can be left out except the ..
+    Lambda {
+        params: Vec<Token>,
+        body: Box<Stmt>,
+        span: Span,
+    }, // fn(x) { ... }
+}
+
+impl Expr {
+    pub fn span(&self) -> Span {
+        match self {
+            Expr::Assign { span, .. }
+            | Expr::Binary { span, .. }
+            | Expr::Call { span, .. }
+            | Expr::Grouping(_, span)
+            | Expr::Literal(_, span)
+            | Expr::Unary { span, .. }
+            | Expr::Range { span, .. }
+            | Expr::Vec(_, span)
+            | Expr::Map(_, span)
+            | Expr::Index { span, .. }
+            | Expr::Get { span, .. }
+            | Expr::Path { span, .. }
+            | Expr::Set { span, .. }
+            | Expr::Struct { span, .. }
+            | Expr::Slice { span, .. }
+            | Expr::Lambda { span, .. } => *span,
+            Expr::Variable(token) => token.span,
+        }
+    }
 }
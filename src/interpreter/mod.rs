@@ -1,3 +1,4 @@
+pub mod cst;
 pub mod expr;
 pub mod parser;
 pub mod scanner;
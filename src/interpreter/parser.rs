@@ -2,85 +2,253 @@
 
 use super::expr::Expr;
 use super::stmt::Stmt;
-use crate::error::KlangError;
+use crate::diagnostic::Diagnostic;
+use crate::error::{ErrorKind, KlangError};
 use crate::scanner::Scanner;
-use crate::scanner::{Token, TokenType, Value};
+use crate::scanner::{Span, Token, TokenType, Value};
 
 pub struct Parser<'a> {
     pub tokens: Vec<Token>,
     current: usize,
-    filename: &'a str,
+    source: &'a str,
+    diagnostics: Vec<Diagnostic>,
+    loops: Vec<Option<String>>, //labels of the loops we are inside of
+    braces: bool, //if a { can start a struct or a map, not in the condition of an if where its the block
 }
 impl<'a> Parser<'a> {
-    pub fn new(tokens: Vec<Token>, filename: &'a str) -> Parser {
+    pub fn new(tokens: Vec<Token>, source: &'a str) -> Parser<'a> {
         Parser {
-            tokens,
+            tokens: attached_docs(tokens),
             current: 0,
-            filename,
+            source,
+            diagnostics: Vec::new(),
+            loops: Vec::new(),
+            braces: true,
         }
     }
-    pub fn parse(&mut self) -> Vec<Stmt> {
+    pub fn parse(&mut self) -> Result<Vec<Stmt>, KlangError> {
         let mut statements: Vec<Stmt> = Vec::new();
         while !self.is_at_end() {
-            statements.push(self.declaration());
+            if let Some(stmt) = self.recover_declaration() {
+                statements.push(stmt);
+            }
+        }
+        if self.diagnostics.is_empty() {
+            Ok(statements)
+        } else {
+            Err(KlangError::new(std::mem::take(&mut self.diagnostics)))
+        }
+    }
+    //parses a declaration, and if it is broken remembers the error and skips
+    //to the next statement so we can keep looking for more errors
+    fn recover_declaration(&mut self) -> Option<Stmt> {
+        let start = self.current;
+        match self.declaration() {
+            Ok(stmt) => Some(stmt),
+            Err(error) => {
+                //an Error token was already reported by the scanner, no need to complain twice.
+                //that goes for hitting the end of the file right after one too, like an unclosed string
+                let reported = self.peek().tt == TokenType::Error
+                    || (self.is_at_end()
+                        && self.current > 0
+                        && self.previous().tt == TokenType::Error);
+                if !reported {
+                    self.diagnostics.extend(error.diagnostics);
+                }
+                self.synchronize(start);
+                None
+            }
+        }
+    }
+    fn synchronize(&mut self, start: usize) {
+        //skip the rest of the broken statement, including any blocks it opened.
+        //stop after a ; or } or right before something that starts a new statement
+        let mut depth = 0;
+        if self.current == start {
+            depth = self.skip(depth);
+        }
+        while !self.is_at_end() {
+            if depth == 0 {
+                if matches!(
+                    self.previous().tt,
+                    TokenType::Semicolon | TokenType::RightBrace
+                ) {
+                    return;
+                }
+                if matches!(
+                    self.peek().tt,
+                    TokenType::Let
+                        | TokenType::Fn
+                        | TokenType::Struct
+                        | TokenType::Impl
+                        | TokenType::If
+                        | TokenType::While
+                        | TokenType::For
+                        | TokenType::Return
+                        | TokenType::Break
+                        | TokenType::Continue
+                        | TokenType::Label
+                        | TokenType::Print
+                        | TokenType::RightBrace
+                ) {
+                    return;
+                }
+            }
+            depth = self.skip(depth);
+        }
+    }
+    fn skip(&mut self, depth: usize) -> usize {
+        match self.advance().tt {
+            TokenType::LeftBrace => depth + 1,
+            TokenType::RightBrace => depth.saturating_sub(1),
+            _ => depth,
         }
-        statements
     }
-    fn declaration(&mut self) -> Stmt {
+    fn declaration(&mut self) -> Result<Stmt, KlangError> {
+        let doc = self.doc_comment();
         if self.match_tokens(&[TokenType::Let]) {
-            self.var_decl()
-        } else if self.match_tokens(&[TokenType::Fn]) {
-            self.fn_decl()
+            self.var_decl(doc)
+        } else if self.check(TokenType::Fn) && self.check_next(TokenType::Identifier) {
+            //fn without a name is a lambda, thats an expression
+            self.advance();
+            self.fn_decl(doc)
+        } else if self.match_tokens(&[TokenType::Struct]) {
+            self.struct_decl(doc)
         } else {
             self.statement()
         }
     }
+    fn doc_comment(&mut self) -> Option<String> {
+        //joins the lines of the /// comments in a row
+        let mut lines: Vec<String> = Vec::new();
+        while self.match_tokens(&[TokenType::DocComment]) {
+            lines.push(self.previous().lexeme);
+        }
+        if lines.is_empty() {
+            None
+        } else {
+            Some(lines.join("\n"))
+        }
+    }
 
-    fn fn_decl(&mut self) -> Stmt {
-        let return_t = self.previous();
-        let name = self.consume(TokenType::Identifier, "must have a function name");
-        if self.match_tokens(&[TokenType::LeftParen]) {
-            if self.match_tokens(&[TokenType::RightParen]) {
-                return Stmt::Fn {
-                    name,
-                    params: Vec::new(),
-                    body: Box::new(self.block()),
-                };
+    fn fn_decl(&mut self, doc: Option<String>) -> Result<Stmt, KlangError> {
+        let start = self.previous().span;
+        let name = self.consume(TokenType::Identifier, "must have a function name")?;
+        let (params, body) = self.function()?;
+        Ok(Stmt::Fn {
+            name,
+            params,
+            body: Box::new(body),
+            span: self.span_from(start),
+            doc,
+        })
+    }
+    fn struct_decl(&mut self, doc: Option<String>) -> Result<Stmt, KlangError> {
+        let start = self.previous().span;
+        let name = self.consume(TokenType::Identifier, "must have a struct name")?;
+        self.consume(TokenType::LeftBrace, "gotta put { after the struct name")?;
+        let mut fields: Vec<Token> = Vec::new();
+        if !self.match_tokens(&[TokenType::RightBrace]) {
+            loop {
+                let field = self.consume(TokenType::Identifier, "field must be an identifier")?;
+                if fields.iter().any(|x| x.lexeme == field.lexeme) {
+                    //no need to give up on the rest of the struct
+                    let error = self.error_at(
+                        field.span,
+                        &format!(
+                            "{} already has a field called {}",
+                            name.lexeme, field.lexeme
+                        ),
+                    );
+                    self.diagnostics.extend(error.diagnostics);
+                }
+                fields.push(field);
+                if !self.match_tokens(&[TokenType::Comma]) {
+                    break;
+                }
+            }
+            self.consume(TokenType::RightBrace, "gotta close the struct")?;
+        }
+        Ok(Stmt::Struct {
+            name,
+            fields,
+            span: self.span_from(start),
+            doc,
+        })
+    }
+    fn impl_stmt(&mut self) -> Result<Stmt, KlangError> {
+        let start = self.previous().span;
+        let name = self.consume(TokenType::Identifier, "must have the name of a struct")?;
+        self.consume(TokenType::LeftBrace, "gotta put { after the struct name")?;
+        let mut methods: Vec<Stmt> = Vec::new();
+        let mut names: Vec<String> = Vec::new();
+        while !self.is_at_end() && !self.check(TokenType::RightBrace) {
+            let doc = self.doc_comment();
+            self.consume(TokenType::Fn, "only functions can go in an impl")?;
+            let method = self.fn_decl(doc)?;
+            if let Stmt::Fn { name: method, .. } = &method {
+                if names.contains(&method.lexeme) {
+                    let error = self.error_at(
+                        method.span,
+                        &format!(
+                            "{} already has a function called {}",
+                            name.lexeme, method.lexeme
+                        ),
+                    );
+                    self.diagnostics.extend(error.diagnostics);
+                }
+                names.push(method.lexeme.clone());
             }
-            let mut vec: Vec<Token> = Vec::new();
-            let mut iden = self.consume(TokenType::Identifier, "argument must be an identifier");
-            vec.push(iden);
+            methods.push(method);
+        }
+        self.consume(TokenType::RightBrace, "gotta close the impl")?;
+        Ok(Stmt::Impl {
+            name,
+            methods,
+            span: self.span_from(start),
+        })
+    }
+    //the (params) { body } of a function or a lambda
+    fn function(&mut self) -> Result<(Vec<Token>, Stmt), KlangError> {
+        self.consume(TokenType::LeftParen, "gotta put ( after the function name")?;
+        let mut params: Vec<Token> = Vec::new();
+        if !self.match_tokens(&[TokenType::RightParen]) {
+            params.push(self.consume(TokenType::Identifier, "argument must be an identifier")?);
             while self.match_tokens(&[TokenType::Comma]) {
-                iden = self.consume(TokenType::Identifier, "parameter must be an identifier");
-                vec.push(iden);
+                params
+                    .push(self.consume(TokenType::Identifier, "parameter must be an identifier")?);
             }
-            self.consume(TokenType::RightParen, "gotta close the call dude");
-            return Stmt::Fn {
-                name,
-                params: vec,
-                body: Box::new(self.block()),
-            };
+            self.consume(TokenType::RightParen, "gotta close the call dude")?;
         }
-        panic!()
+        //loops outside of the function cant be broken out of from inside it
+        let loops = std::mem::take(&mut self.loops);
+        let body = self.block();
+        self.loops = loops;
+        Ok((params, body?))
     }
-    fn var_decl(&mut self) -> Stmt {
-        let name = self.consume(TokenType::Identifier, "must define a variable name");
+    fn var_decl(&mut self, doc: Option<String>) -> Result<Stmt, KlangError> {
+        let start = self.previous().span;
+        let name = self.consume(TokenType::Identifier, "must define a variable name")?;
         if self.match_tokens(&[TokenType::Equal]) {
-            let value = self.logical();
-            self.consume(TokenType::Semicolon, "missing ; at the end of the line");
-            return Stmt::Var {
+            let value = self.expression()?;
+            self.consume(TokenType::Semicolon, "missing ; at the end of the line")?;
+            return Ok(Stmt::Var {
                 name,
                 value: Some(value),
-            };
+                span: self.span_from(start),
+                doc,
+            });
         }
-        self.consume(TokenType::Semicolon, "missing ; at the end of the line");
-        Stmt::Var {
+        self.consume(TokenType::Semicolon, "missing ; at the end of the line")?;
+        Ok(Stmt::Var {
             name,
             value: None::<Expr>,
-        }
+            span: self.span_from(start),
+            doc,
+        })
     }
 
-    fn statement(&mut self) -> Stmt {
+    fn statement(&mut self) -> Result<Stmt, KlangError> {
         if self.match_tokens(&[TokenType::Print]) {
             self.print_stmt()
         } else if self.check(TokenType::LeftBrace) {
@@ -88,356 +256,627 @@ impl<'a> Parser<'a> {
         } else if self.match_tokens(&[TokenType::If]) {
             self.if_stmt()
         } else if self.match_tokens(&[TokenType::While]) {
-            self.while_stmt()
+            self.while_stmt(None)
         } else if self.match_tokens(&[TokenType::For]) {
-            self.for_stmt()
+            self.for_stmt(None)
+        } else if self.match_tokens(&[TokenType::Label]) {
+            let label = self.previous();
+            self.consume(TokenType::Colon, "missing : after the label")?;
+            if self.match_tokens(&[TokenType::While]) {
+                self.while_stmt(Some(label))
+            } else if self.match_tokens(&[TokenType::For]) {
+                self.for_stmt(Some(label))
+            } else {
+                Err(self.error_at(label.span, "only loops can have a label"))
+            }
+        } else if self.match_tokens(&[TokenType::Impl]) {
+            self.impl_stmt()
         } else if self.match_tokens(&[TokenType::Return]) {
             self.return_stmt()
+        } else if self.match_tokens(&[TokenType::Break, TokenType::Continue]) {
+            self.jump_stmt()
         } else {
             self.expr_stmt()
         }
     }
 
-    fn return_stmt(&mut self) -> Stmt {
+    fn return_stmt(&mut self) -> Result<Stmt, KlangError> {
+        let start = self.previous().span;
         if self.match_tokens(&[TokenType::Semicolon]) {
-            return Stmt::Return(None, self.previous().line);
+            return Ok(Stmt::Return(None, self.span_from(start)));
         }
-        let value = self.logical();
-        self.consume(TokenType::Semicolon, "missing ; at the end of lien");
-        Stmt::Return(Some(value), self.previous().line)
+        let value = self.expression()?;
+        self.consume(TokenType::Semicolon, "missing ; at the end of lien")?;
+        Ok(Stmt::Return(Some(value), self.span_from(start)))
     }
 
-    fn for_stmt(&mut self) -> Stmt {
-        let identifier = self.consume(TokenType::Identifier, "missing identifier 8=D");
-        let line = self.previous().line;
-        self.consume(TokenType::In, "missing in");
-        let iterable = self.range();
-        match iterable {
-            Expr::Range {
-                min: _,
-                max: _,
-                step: _,
-                line: _,
-            } => (),
-            _ => self.error("\"in\" must be used on an iterable"),
+    fn jump_stmt(&mut self) -> Result<Stmt, KlangError> {
+        let keyword = self.previous();
+        let label = if self.match_tokens(&[TokenType::Label]) {
+            Some(self.previous())
+        } else {
+            None
+        };
+        self.consume(TokenType::Semicolon, "missing ; at the end of the line")?;
+        let span = self.span_from(keyword.span);
+        if self.loops.is_empty() {
+            return Err(self.error_at(span, &format!("cant {} outside of a loop", keyword.tt)));
+        }
+        if let Some(label) = &label {
+            if !self.loops.contains(&Some(label.lexeme.clone())) {
+                return Err(KlangError::from(
+                    Diagnostic::new(
+                        ErrorKind::ParserError,
+                        &format!("there is no loop called {}", label.lexeme),
+                        label.span,
+                    )
+                    .help("labels go before the loop, like 'outer: while ..."),
+                ));
+            }
+        }
+        if keyword.tt == TokenType::Break {
+            Ok(Stmt::Break(label, span))
+        } else {
+            Ok(Stmt::Continue(label, span))
         }
+    }
+    //parses the body of a loop, break and continue are only allowed in here
+    fn loop_block(&mut self, label: &Option<Token>) -> Result<Stmt, KlangError> {
+        self.loops
+            .push(label.as_ref().map(|label| label.lexeme.clone()));
+        let block = self.block();
+        self.loops.pop();
+        block
+    }
+    fn for_stmt(&mut self, label: Option<Token>) -> Result<Stmt, KlangError> {
+        let start = label
+            .as_ref()
+            .map_or(self.previous().span, |label| label.span);
+        let identifier = self.consume(TokenType::Identifier, "missing identifier 8=D")?;
+        let value = if self.match_tokens(&[TokenType::Comma]) {
+            Some(self.consume(TokenType::Identifier, "missing identifier after the ,")?)
+        } else {
+            None
+        };
+        self.consume(TokenType::In, "missing in")?;
+        let iterable = Box::new(self.condition()?);
 
-        let block = Box::new(self.block());
-        Stmt::For {
+        let block = Box::new(self.loop_block(&label)?);
+        Ok(Stmt::For {
             identifier,
+            value,
             iterable,
             block,
-            line,
-        }
+            span: self.span_from(start),
+            label,
+        })
     }
 
-    fn if_stmt(&mut self) -> Stmt {
-        let condition = self.logical();
-        let start = self.previous().line;
-        let block = Box::new(self.block());
+    fn if_stmt(&mut self) -> Result<Stmt, KlangError> {
+        let start = self.previous().span;
+        let condition = self.condition()?;
+        let block = Box::new(self.block()?);
         if self.match_tokens(&[TokenType::Else]) {
-            let end = self.previous().line;
-            let elseblock = Some(Box::new(self.block()));
-            return Stmt::If {
+            let end = self.previous().span;
+            //else if is an if statement as the else block, no extra block around it
+            let elseblock = if self.match_tokens(&[TokenType::If]) {
+                Some(Box::new(self.if_stmt()?))
+            } else {
+                Some(Box::new(self.block()?))
+            };
+            return Ok(Stmt::If {
                 condition,
                 block,
                 elseblock,
-                lines: (start, Some(end)),
-            };
+                spans: (start, Some(end)),
+            });
         }
-        Stmt::If {
+        Ok(Stmt::If {
             condition,
             block,
             elseblock: None,
-            lines: (start, None),
-        }
+            spans: (start, None),
+        })
     }
 
-    fn while_stmt(&mut self) -> Stmt {
-        let condition = self.logical();
-        let line = self.previous().line;
-        let block = self.block();
+    fn while_stmt(&mut self, label: Option<Token>) -> Result<Stmt, KlangError> {
+        let start = label
+            .as_ref()
+            .map_or(self.previous().span, |label| label.span);
+        let condition = self.condition()?;
+        let block = self.loop_block(&label)?;
 
-        Stmt::While {
+        Ok(Stmt::While {
             condition,
             block: Box::new(block),
-            line,
-        }
+            span: self.span_from(start),
+            label,
+        })
     }
 
-    fn block(&mut self) -> Stmt {
-        self.consume(TokenType::LeftBrace, "must start block with a {");
-        let start = self.previous().line;
+    //the expression before the block of an if, while or for. Point {} would eat
+    //the block so it isnt a struct in here, Point { x: 1 } still is
+    fn condition(&mut self) -> Result<Expr, KlangError> {
+        let braces = std::mem::replace(&mut self.braces, false);
+        let condition = self.expression();
+        self.braces = braces;
+        condition
+    }
+    //inside brackets a { cant be the block of an if, so structs and maps work again
+    fn bracketed(&mut self) -> Result<Expr, KlangError> {
+        let braces = std::mem::replace(&mut self.braces, true);
+        let expression = self.expression();
+        self.braces = braces;
+        expression
+    }
+
+    fn block(&mut self) -> Result<Stmt, KlangError> {
+        self.consume(TokenType::LeftBrace, "must start block with a {")?;
+        let start = self.previous().span;
+        let braces = std::mem::replace(&mut self.braces, true);
         let mut statements: Vec<Stmt> = Vec::new();
         while !self.is_at_end() && !self.check(TokenType::RightBrace) {
-            statements.push(self.declaration());
+            if let Some(stmt) = self.recover_declaration() {
+                statements.push(stmt);
+            }
         }
-        self.consume(TokenType::RightBrace, "must end block with a }");
-        Stmt::Block(statements, (start, self.previous().line))
+        self.braces = braces;
+        self.consume(TokenType::RightBrace, "must end block with a }")?;
+        Ok(Stmt::Block(statements, (start, self.previous().span)))
     }
 
-    fn print_stmt(&mut self) -> Stmt {
+    fn print_stmt(&mut self) -> Result<Stmt, KlangError> {
+        let start = self.previous().span;
         self.consume(
             TokenType::LeftParen,
             "gotta put ( after a print yk how it is..",
-        );
-        let stmt = Stmt::Print(
-            match self.primary() {
-                Expr::Literal(Value::String { string, printables }, _) => {
-                    Value::String { string, printables }
-                }
-                _ => {
-                    self.error("can only print strings");
-                    panic!("balls")
-                }
-            },
-            self.peek().line,
-        );
+        )?;
+        let value = match self.primary()? {
+            Expr::Literal(Value::String { string, printables }, _) => {
+                Value::String { string, printables }
+            }
+            other => return Err(self.error_at(other.span(), "can only print strings")),
+        };
         self.consume(
             TokenType::RightParen,
             "gotta put ) at the end of a print yk how it is..",
-        );
-        self.consume(TokenType::Semicolon, "missing ; at the end of the line");
-        stmt
+        )?;
+        self.consume(TokenType::Semicolon, "missing ; at the end of the line")?;
+        Ok(Stmt::Print(value, self.span_from(start)))
     }
-    fn expr_stmt(&mut self) -> Stmt {
-        let stmt = Stmt::Expression(self.assignment());
-        self.consume(TokenType::Semicolon, "missing ; at the end of the line");
-        stmt
+    fn expr_stmt(&mut self) -> Result<Stmt, KlangError> {
+        let stmt = Stmt::Expression(self.assignment()?);
+        self.consume(TokenType::Semicolon, "missing ; at the end of the line")?;
+        Ok(stmt)
     }
 
-    pub fn assignment(&mut self) -> Expr {
-        let identifier = self.logical();
-        if self.match_tokens(&[TokenType::Equal]) {
-            let value = self.logical();
-            match identifier {
-                Expr::Variable(name) => {
-                    return Expr::Assign {
-                        name,
-                        value: Box::new(value),
-                    }
-                }
-                _ => {
-                    self.error("cannot assign to a non variable");
-                    panic!();
-                }
+    pub fn assignment(&mut self) -> Result<Expr, KlangError> {
+        let identifier = self.expression()?;
+        let (operator, value) = if self.match_tokens(&[
+            TokenType::Equal,
+            TokenType::PlusEqual,
+            TokenType::MinusEqual,
+            TokenType::StarEqual,
+            TokenType::SlashEqual,
+            TokenType::ModuloEqual,
+        ]) {
+            //x += 1 keeps the + so the compiler can do x = x + 1 without working out x twice
+            (compound(&self.previous()), self.expression()?)
+        } else if self.match_tokens(&[TokenType::PlusPlus, TokenType::MinusMinus]) {
+            //x++ is x += 1
+            let token = self.previous();
+            (
+                compound(&token),
+                Expr::Literal(Value::Number(1.0), token.span),
+            )
+        } else {
+            return Ok(identifier);
+        };
+        match identifier {
+            Expr::Variable(name) => {
+                let span = name.span.to(value.span());
+                Ok(Expr::Assign {
+                    name,
+                    value: Box::new(value),
+                    operator,
+                    span,
+                })
+            }
+            Expr::Index { .. } | Expr::Get { .. } if assignable(&identifier) => {
+                let span = identifier.span().to(value.span());
+                Ok(Expr::Set {
+                    target: Box::new(identifier),
+                    value: Box::new(value),
+                    operator,
+                    span,
+                })
             }
+            _ => Err(self.error_at(identifier.span(), "cannot assign to a non variable")),
         }
-        identifier
     }
 
-    pub fn logical(&mut self) -> Expr {
-        let left: Expr = self.equality();
-        if self.match_tokens(&[TokenType::And, TokenType::Or]) {
-            let operator = self.previous();
-            let right: Expr = self.logical();
-            return Expr::Binary {
-                left: Box::new(left),
-                operator,
-                right: Box::new(right),
-            };
-        }
-        left
+    pub fn expression(&mut self) -> Result<Expr, KlangError> {
+        self.binary(0)
     }
-
-    fn equality(&mut self) -> Expr {
-        let left: Expr = self.comparison();
-        if self.match_tokens(&[TokenType::BangEqual, TokenType::EqualEqual]) {
-            let operator = self.previous();
-            let right: Expr = self.comparison();
-            return Expr::Binary {
-                left: Box::new(left),
-                operator,
-                right: Box::new(right),
-            };
-        }
-        left
-    }
-    fn comparison(&mut self) -> Expr {
-        let left: Expr = self.term();
-        if self.match_tokens(&[
-            TokenType::Greater,
-            TokenType::GreaterEqual,
-            TokenType::Less,
-            TokenType::LessEqual,
-        ]) {
-            let operator = self.previous();
-            let right: Expr = self.term();
-            return Expr::Binary {
-                left: Box::new(left),
-                operator,
-                right: Box::new(right),
+    fn binary(&mut self, min: u8) -> Result<Expr, KlangError> {
+        //precedence climbing: keep folding operators into the left side while they bind
+        //at least as tight as min. the right side only takes tighter operators, which
+        //makes everything left associative (10 - 3 - 2 is (10 - 3) - 2)
+        let mut left = self.unary()?;
+        loop {
+            //a--b is a - -b, but the -- of x--; is left for the decrement
+            if self.check(TokenType::MinusMinus) && self.operand_next() {
+                self.split_minus();
+            }
+            let Some(precedence) = precedence(self.peek().tt) else {
+                break;
             };
-        }
-        left
-    }
-    fn term(&mut self) -> Expr {
-        let left: Expr = self.factor();
-        if self.match_tokens(&[TokenType::Plus, TokenType::Minus]) {
-            let operator = self.previous();
-            let right: Expr = self.term();
-            return Expr::Binary {
+            if precedence < min {
+                break;
+            }
+            let operator = self.advance();
+            if operator.tt == TokenType::Range {
+                if let Expr::Range { .. } = left {
+                    return Err(Diagnostic::new(
+                        ErrorKind::ParserError,
+                        "ranges only take a start, an end and a step",
+                        operator.span,
+                    )
+                    .help("ranges look like start..end or start..end..step")
+                    .into());
+                }
+                left = self.range(left, precedence + 1)?;
+                continue;
+            }
+            let right = self.binary(precedence + 1)?;
+            let span = left.span().to(right.span());
+            left = Expr::Binary {
                 left: Box::new(left),
                 operator,
                 right: Box::new(right),
+                span,
             };
         }
-        left
+        Ok(left)
     }
-    fn factor(&mut self) -> Expr {
-        let left: Expr = self.range();
-        if self.match_tokens(&[TokenType::Slash, TokenType::Star, TokenType::Modulo]) {
-            let operator = self.previous();
-            let right: Expr = self.factor();
-            return Expr::Binary {
-                left: Box::new(left),
-                operator,
-                right: Box::new(right),
-            };
-        }
-        left
+    fn range(&mut self, start: Expr, precedence: u8) -> Result<Expr, KlangError> {
+        //min..max or min..max..step, the first .. is already consumed
+        let span = start.span();
+        let end = self.binary(precedence)?;
+        let step = if self.match_tokens(&[TokenType::Range]) {
+            Some(Box::new(self.binary(precedence)?))
+        } else {
+            None
+        };
+        Ok(Expr::Range {
+            min: Box::new(start),
+            max: Box::new(end),
+            step,
+            span: self.span_from(span),
+        })
     }
-    pub fn range(&mut self) -> Expr {
-        let start = self.unary();
-        if self.match_tokens(&[TokenType::Range]) {
-            match &start {
-                Expr::Literal(Value::Number(_), _) | Expr::Variable(_) => {}
-                _ => {
-                    self.error("you can only index a range using an integer");
-                    panic!();
-                }
-            }
-            let end = self.unary();
-            match &end {
-                Expr::Literal(Value::Number(_), _) | Expr::Variable(_) => {}
-                _ => {
-                    self.error("you can only index a range using an integer");
-                    panic!();
-                }
-            }
-            if self.match_tokens(&[TokenType::Range]) {
-                let step = self.unary();
-                match &step {
-                    Expr::Literal(Value::Number(_), _) | Expr::Variable(_) => {}
-                    _ => {
-                        self.error("you can only index a range using an integer");
-                        panic!();
-                    }
-                }
-                return Expr::Range {
-                    min: Box::new(start),
-                    max: Box::new(end),
-                    step: Some(Box::new(step)),
-                    line: self.previous().line,
-                };
-            }
-            return Expr::Range {
-                min: Box::new(start),
-                max: Box::new(end),
-                step: None,
-                line: self.previous().line,
-            };
+    fn unary(&mut self) -> Result<Expr, KlangError> {
+        if self.check(TokenType::MinusMinus) {
+            self.split_minus(); //--x is - -x
         }
-        start
-    }
-    fn unary(&mut self) -> Expr {
         if self.match_tokens(&[TokenType::Bang, TokenType::Minus]) {
             let operator = self.previous();
-            let e = self.primary();
-            return Expr::Unary {
+            let e = self.unary()?;
+            let span = operator.span.to(e.span());
+            return Ok(Expr::Unary {
                 operator,
                 expression: Box::new(e),
-            };
+                span,
+            });
         }
         self.call(false)
     }
 
-    fn call(&mut self, native: bool) -> Expr {
-        let expr = self.primary();
-        if self.match_tokens(&[TokenType::LeftParen]) {
-            if !matches!(expr, Expr::Variable(_)) {
-                self.error("sir were you trying to call a function USING AN INTEGER?")
-            }
-            if self.match_tokens(&[TokenType::RightParen]) {
-                return Expr::Call {
-                    callee: Box::new(expr),
-                    arguments: Vec::new(),
-                    native,
+    fn call(&mut self, native: bool) -> Result<Expr, KlangError> {
+        let start = if native {
+            self.previous().span //include the std::
+        } else {
+            self.peek().span
+        };
+        let mut expr = self.primary()?;
+        let mut native = native;
+        //any number of calls, [i], .x and ::f after a value, f(1)(2) calls what f(1) gives back
+        //and v[i][j] indexes what v[i] gives back
+        loop {
+            if self.match_tokens(&[TokenType::LeftParen]) {
+                expr = self.call_args(expr, start, native)?;
+                native = false; //only std::f is native, not what it gives back
+            } else if self.match_tokens(&[TokenType::LeftSquare]) {
+                expr = self.index(expr, start)?;
+            } else if self.match_tokens(&[TokenType::Dot]) {
+                let name = self.consume(TokenType::Identifier, "missing field name after the .")?;
+                expr = Expr::Get {
+                    object: Box::new(expr),
+                    name,
+                    span: self.span_from(start),
+                };
+            } else if self.match_tokens(&[TokenType::DoubleColon]) {
+                let name =
+                    self.consume(TokenType::Identifier, "missing function name after the ::")?;
+                expr = Expr::Path {
+                    object: Box::new(expr),
+                    name,
+                    span: self.span_from(start),
                 };
+            } else {
+                return Ok(expr);
             }
-            let mut vec: Vec<Expr> = Vec::new();
-            vec.push(self.logical());
+        }
+    }
+    fn index(&mut self, expr: Expr, start: Span) -> Result<Expr, KlangError> {
+        //the parts of a slice are parsed above ranges so the .. is left for us
+        let index = if self.check(TokenType::Range) {
+            None
+        } else {
+            Some(Box::new(self.binary(2)?))
+        };
+        if self.match_tokens(&[TokenType::Range]) {
+            return self.slice(expr, index, start);
+        }
+        self.consume(TokenType::RightSquare, "missing ] after the index")?;
+        Ok(Expr::Index {
+            object: Box::new(expr),
+            index: index.unwrap(),
+            span: self.span_from(start),
+        })
+    }
+    fn slice(
+        &mut self,
+        object: Expr,
+        start: Option<Box<Expr>>,
+        span: Span,
+    ) -> Result<Expr, KlangError> {
+        //v[a..b..s], the first .. is already consumed
+        let end = if self.check(TokenType::RightSquare) || self.check(TokenType::Range) {
+            None
+        } else {
+            Some(Box::new(self.binary(2)?))
+        };
+        let step = if self.match_tokens(&[TokenType::Range]) {
+            Some(Box::new(self.binary(2)?))
+        } else {
+            None
+        };
+        self.consume(TokenType::RightSquare, "missing ] after the slice")?;
+        Ok(Expr::Slice {
+            object: Box::new(object),
+            start,
+            end,
+            step,
+            span: self.span_from(span),
+        })
+    }
+    fn call_args(&mut self, expr: Expr, start: Span, native: bool) -> Result<Expr, KlangError> {
+        //anything can be called as long as it gives back a function, except a literal
+        if matches!(expr, Expr::Literal(..)) {
+            return Err(self.error_at(
+                expr.span(),
+                "sir were you trying to call a function USING AN INTEGER?",
+            ));
+        }
+        if native && !matches!(expr, Expr::Variable(_)) {
+            return Err(self.error_at(expr.span(), "std:: must be followed by a function name"));
+        }
+        let mut vec: Vec<Expr> = Vec::new();
+        if !self.match_tokens(&[TokenType::RightParen]) {
+            vec.push(self.bracketed()?);
             while self.match_tokens(&[TokenType::Comma]) {
-                vec.push(self.logical());
+                vec.push(self.bracketed()?);
             }
-            self.consume(TokenType::RightParen, "gotta close the call dude");
-            return Expr::Call {
-                callee: Box::new(expr),
-                arguments: vec,
-                native,
-            };
+            self.consume(TokenType::RightParen, "gotta close the call dude")?;
         }
-        expr
+        Ok(Expr::Call {
+            span: self.span_from(start),
+            callee: Box::new(expr),
+            arguments: vec,
+            native,
+        })
     }
 
-    fn primary(&mut self) -> Expr {
-        if self.match_tokens(&[TokenType::Bool]) {
-            if self.previous().lexeme == "true" {
-                return Expr::Literal(Value::Bool(true), self.previous().line);
-            } else {
-                return Expr::Literal(Value::Bool(false), self.previous().line);
-            }
+    fn primary(&mut self) -> Result<Expr, KlangError> {
+        if self.match_tokens(&[TokenType::Bool, TokenType::Int, TokenType::Float]) {
+            //true, false and numbers come with their value, the type names dont
+            return match self.previous().literal {
+                Some(value) => Ok(Expr::Literal(value, self.previous().span)),
+                None => Err(self.type_keyword()),
+            };
         }
         if self.match_tokens(&[TokenType::LeftSquare]) {
+            let start = self.previous().span;
             let mut vec: Vec<Expr> = Vec::new();
-            vec.push(self.logical());
+            vec.push(self.bracketed()?);
             while self.match_tokens(&[TokenType::Comma]) {
-                vec.push(self.logical());
+                vec.push(self.bracketed()?);
+            }
+            self.consume(TokenType::RightSquare, "gotta close the vec")?;
+            return Ok(Expr::Vec(vec, self.span_from(start)));
+        }
+        //a { where a value goes is a map, blocks are only statements. in a condition its the
+        //block unless a key and a : come after it, a block cant start like that
+        if self.check(TokenType::LeftBrace) && (self.braces || self.check_nth(2, TokenType::Colon))
+        {
+            self.advance();
+            let start = self.previous().span;
+            let mut entries: Vec<(Expr, Expr)> = Vec::new();
+            if !self.match_tokens(&[TokenType::RightBrace]) {
+                loop {
+                    let key = self.bracketed()?;
+                    self.consume(TokenType::Colon, "missing : between the key and the value")?;
+                    entries.push((key, self.bracketed()?));
+                    if !self.match_tokens(&[TokenType::Comma]) {
+                        break;
+                    }
+                }
+                self.consume(TokenType::RightBrace, "gotta close the map")?;
             }
-            self.consume(TokenType::RightSquare, "gotta close the vec");
-            return Expr::Vec(vec);
+            return Ok(Expr::Map(entries, self.span_from(start)));
         }
         if self.match_tokens(&[TokenType::String]) {
+            let span = self.previous().span;
+            if &self.source[span.start..span.end] == "string" {
+                return Err(self.type_keyword());
+            }
             let string = self.previous().lexeme;
             let mut printables_t: Vec<Vec<Token>> = Vec::new();
             while self.match_tokens(&[TokenType::Printable]) {
-                let lexeme = self.previous().lexeme;
-                if lexeme.contains('"') {
-                    self.error("why would you use a string inside a string?? are you retarded??");
+                let printable = self.previous();
+                if printable.lexeme.contains('"') {
+                    return Err(self.error_at(
+                        printable.span,
+                        "why would you use a string inside a string?? are you retarded??",
+                    ));
                 }
-                let mut s = Scanner::new(&lexeme, self.filename);
-                let mut s1 = s.scan_tokens();
+                let mut s = Scanner::nested(self.source, printable.span);
+                let (mut s1, diagnostics) = s.scan_tokens();
+                self.diagnostics.extend(diagnostics);
                 s1.pop();
                 printables_t.push(s1);
-                self.match_tokens(&[TokenType::Comma]);
             }
             let mut printables: Vec<Expr> = Vec::new();
             for i in printables_t {
                 self.tokens.splice(self.current..self.current, i);
-                printables.push(self.logical());
+                printables.push(self.expression()?);
             }
-            return Expr::Literal(Value::String { string, printables }, self.previous().line);
+            return Ok(Expr::Literal(Value::String { string, printables }, span));
         }
 
-        if self.match_tokens(&[TokenType::Int, TokenType::Float]) {
-            return Expr::Literal(self.previous().literal.unwrap(), self.previous().line);
-        }
         if self.match_tokens(&[TokenType::LeftParen]) {
-            let expression = self.logical();
+            let start = self.previous().span;
+            let expression = self.bracketed()?;
             self.consume(
                 TokenType::RightParen,
                 "expected \")\" after expression u piece of shit",
-            );
-            return Expr::Grouping(Box::new(expression));
+            )?;
+            return Ok(Expr::Grouping(Box::new(expression), self.span_from(start)));
         }
         if self.match_tokens(&[TokenType::NativeCall]) {
             return self.call(true);
         }
+        if self.match_tokens(&[TokenType::Fn]) {
+            let start = self.previous().span;
+            let (params, body) = self.function()?;
+            return Ok(Expr::Lambda {
+                params,
+                body: Box::new(body),
+                span: self.span_from(start),
+            });
+        }
         if self.match_tokens(&[TokenType::Identifier]) {
-            return Expr::Variable(self.previous());
+            //Point { x: ... or Point {}
+            if self.check(TokenType::LeftBrace)
+                && ((self.check_nth(1, TokenType::Identifier)
+                    && self.check_nth(2, TokenType::Colon))
+                    || (self.braces && self.check_nth(1, TokenType::RightBrace)))
+            {
+                return self.struct_literal();
+            }
+            return Ok(Expr::Variable(self.previous()));
+        }
+        if self.check(TokenType::PlusPlus) {
+            return Err(Diagnostic::new(
+                ErrorKind::ParserError,
+                "++ goes after what it adds to",
+                self.peek().span,
+            )
+            .help("write x++; or x += 1;")
+            .into());
+        }
+        if self.match_tokens(&[TokenType::Error]) {
+            //stand in for whatever the scanner couldnt read so we can keep parsing
+            return Ok(Expr::Literal(Value::None, self.previous().span));
         }
-        self.error(&format!("expected value found {}", self.peek().tt));
-        panic!("cock!")
+        if self.check(TokenType::LeftBrace) {
+            return Err(Diagnostic::new(
+                ErrorKind::ParserError,
+                "expected a value before the {",
+                self.peek().span,
+            )
+            .note("a { right after if, while or for ... in starts the block")
+            .help("put the condition between the keyword and the {, or the map in ( )")
+            .into());
+        }
+        Err(self.error(&format!("expected value found {}", self.peek().tt)))
+    }
+
+    //if the token after peek can start a value
+    fn operand_next(&self) -> bool {
+        self.tokens.get(self.current + 1).is_some_and(|token| {
+            matches!(
+                token.tt,
+                TokenType::Identifier
+                    | TokenType::Int
+                    | TokenType::Float
+                    | TokenType::String
+                    | TokenType::Bool
+                    | TokenType::LeftParen
+                    | TokenType::LeftSquare
+                    | TokenType::LeftBrace
+                    | TokenType::Minus
+                    | TokenType::MinusMinus
+                    | TokenType::Bang
+                    | TokenType::Fn
+                    | TokenType::NativeCall
+            )
+        })
+    }
+    //-- is one token so x--; can be a decrement, anywhere else its two minuses
+    fn split_minus(&mut self) {
+        let span = self.peek().span;
+        let minus = |span| Token {
+            tt: TokenType::Minus,
+            lexeme: "-".to_string(),
+            literal: None,
+            span,
+        };
+        let first = Span {
+            end: span.start + 1,
+            end_line: span.line,
+            end_column: span.column + 1,
+            ..span
+        };
+        let second = Span {
+            start: span.start + 1,
+            column: span.column + 1,
+            ..span
+        };
+        self.tokens
+            .splice(self.current..=self.current, [minus(first), minus(second)]);
+    }
+    fn struct_literal(&mut self) -> Result<Expr, KlangError> {
+        let name = self.previous();
+        self.advance();
+        let mut fields: Vec<(Token, Expr)> = Vec::new();
+        if !self.match_tokens(&[TokenType::RightBrace]) {
+            loop {
+                let field = self.consume(TokenType::Identifier, "field must be an identifier")?;
+                if fields.iter().any(|(x, _)| x.lexeme == field.lexeme) {
+                    let error = self.error_at(
+                        field.span,
+                        &format!("the field {} is given twice", field.lexeme),
+                    );
+                    self.diagnostics.extend(error.diagnostics);
+                }
+                self.consume(
+                    TokenType::Colon,
+                    "missing : between the field and the value",
+                )?;
+                fields.push((field, self.bracketed()?));
+                if !self.match_tokens(&[TokenType::Comma]) {
+                    break;
+                }
+            }
+            self.consume(TokenType::RightBrace, "gotta close the struct")?;
+        }
+        Ok(Expr::Struct {
+            span: self.span_from(name.span),
+            name,
+            fields,
+        })
     }
 
     fn match_tokens(&mut self, types: &[TokenType]) -> bool {
@@ -456,6 +895,15 @@ impl<'a> Parser<'a> {
         }
         self.peek().tt == t_type
     }
+    fn check_next(&self, t_type: TokenType) -> bool {
+        //the token after peek
+        self.check_nth(1, t_type)
+    }
+    fn check_nth(&self, n: usize, t_type: TokenType) -> bool {
+        self.tokens
+            .get(self.current + n)
+            .is_some_and(|token| token.tt == t_type)
+    }
 
     fn advance(&mut self) -> Token {
         if !self.is_at_end() {
@@ -472,19 +920,130 @@ impl<'a> Parser<'a> {
     fn previous(&self) -> Token {
         self.tokens[self.current - 1].clone()
     }
-    fn error(&self, msg: &str) {
-        KlangError::error(
-            KlangError::ParserError,
-            msg,
-            self.peek().line,
-            self.filename,
-        );
+    fn span_from(&self, start: Span) -> Span {
+        //the span from start up to the last consumed token
+        start.to(self.previous().span)
+    }
+    fn error(&self, msg: &str) -> KlangError {
+        self.error_at(self.peek().span, msg)
+    }
+    fn error_at(&self, span: Span, msg: &str) -> KlangError {
+        Diagnostic::new(ErrorKind::ParserError, msg, span).into()
     }
-    fn consume(&mut self, t_type: TokenType, msg: &str) -> Token {
+    //int, float, string and bool are keywords, the previous token is one of them used as a value
+    fn type_keyword(&self) -> KlangError {
+        let span = self.previous().span;
+        let name = &self.source[span.start..span.end];
+        Diagnostic::new(
+            ErrorKind::ParserError,
+            &format!("expected a value, found type `{name}`"),
+            span,
+        )
+        .note("int, float, string and bool are names of types, not variables")
+        .into()
+    }
+    fn consume(&mut self, t_type: TokenType, msg: &str) -> Result<Token, KlangError> {
         if self.peek().tt == t_type {
-            return self.advance();
+            return Ok(self.advance());
+        }
+        let found = self.peek();
+        if matches!(found.tt, TokenType::PlusPlus | TokenType::MinusMinus) {
+            return Err(Diagnostic::new(
+                ErrorKind::ParserError,
+                &format!(
+                    "{} only works as a statement, it doesnt give back a value",
+                    found.lexeme
+                ),
+                found.span,
+            )
+            .help(&format!("put it on its own line, like x{};", found.lexeme))
+            .into());
+        }
+        if t_type == TokenType::Semicolon && self.current > 0 {
+            //point right after the end of the statement instead of at the next one
+            let end = self.previous().span;
+            let span = Span {
+                start: end.end,
+                line: end.end_line,
+                column: end.end_column,
+                ..end
+            };
+            Err(Diagnostic::new(ErrorKind::ParserError, msg, span)
+                .help("add a ; at the end of the statement")
+                .into())
+        } else {
+            Err(Diagnostic::new(ErrorKind::ParserError, msg, found.span)
+                .note(&format!("expected {t_type} but found {}", found.tt))
+                .into())
         }
-        self.error(msg);
-        panic!()
     }
 }
+
+//a /// thats not right above a fn, let or struct is just a comment, so it goes
+//before the parser ever sees it (it could be in the middle of a vector)
+fn attached_docs(tokens: Vec<Token>) -> Vec<Token> {
+    let mut kept = Vec::with_capacity(tokens.len());
+    for (i, token) in tokens.iter().enumerate() {
+        if token.tt == TokenType::DocComment {
+            let mut next = tokens[i..]
+                .iter()
+                .map(|token| token.tt)
+                .skip_while(|tt| *tt == TokenType::DocComment);
+            let attached = matches!(
+                (next.next(), next.next()),
+                (Some(TokenType::Let | TokenType::Struct), _)
+                    | (Some(TokenType::Fn), Some(TokenType::Identifier))
+            );
+            if !attached {
+                continue;
+            }
+        }
+        kept.push(token.clone());
+    }
+    kept
+}
+
+//how tight each binary operator binds, higher binds tighter
+fn precedence(tt: TokenType) -> Option<u8> {
+    match tt {
+        TokenType::Range => Some(1),
+        TokenType::Or => Some(2),
+        TokenType::And => Some(3),
+        TokenType::EqualEqual | TokenType::BangEqual => Some(4),
+        TokenType::Greater | TokenType::GreaterEqual | TokenType::Less | TokenType::LessEqual => {
+            Some(5)
+        }
+        TokenType::Plus | TokenType::Minus => Some(6),
+        TokenType::Star | TokenType::Slash | TokenType::Modulo => Some(7),
+        _ => None,
+    }
+}
+
+//a variable or an index or field of one, what can go on the left of v[i].x = y
+fn assignable(expr: &Expr) -> bool {
+    match expr {
+        Expr::Variable(_) => true,
+        Expr::Index { object, .. } | Expr::Get { object, .. } => assignable(object),
+        _ => false,
+    }
+}
+
+//the operator hiding in +=, -= and friends
+fn compound(token: &Token) -> Option<Token> {
+    let tt = match token.tt {
+        TokenType::PlusEqual => TokenType::Plus,
+        TokenType::MinusEqual => TokenType::Minus,
+        TokenType::StarEqual => TokenType::Star,
+        TokenType::SlashEqual => TokenType::Slash,
+        TokenType::ModuloEqual => TokenType::Modulo,
+        TokenType::PlusPlus => TokenType::Plus,
+        TokenType::MinusMinus => TokenType::Minus,
+        _ => return None,
+    };
+    Some(Token {
+        tt,
+        lexeme: token.lexeme[..1].to_string(),
+        literal: None,
+        span: token.span,
+    })
+}
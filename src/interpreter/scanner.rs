@@ -1,378 +1,547 @@
 #![allow(unused)]
 use super::expr::Expr;
-use crate::{error, KlangError};
-use std::collections::HashMap;
+use crate::diagnostic::Diagnostic;
+use crate::error::ErrorKind;
+use std::cell::RefCell;
+use std::cmp::Ordering;
+use std::collections::{BTreeMap, HashMap};
 use std::fmt;
-use std::iter::Peekable;
-use std::str::Chars;
+use std::rc::Rc;
+use unicode_xid::UnicodeXID;
 
 #[derive(Debug, Clone)]
 pub struct Scanner<'a> {
     pub source: &'a str,
-    pub chars: Peekable<Chars<'a>>,
+    pub current: usize,
     pub line: usize,
+    pub column: usize,
     pub tokens: Vec<Token>,
-    filename: &'a str,
-    had_error: bool,
+    end: usize,                   //byte offset where scanning stops
+    start: (usize, usize, usize), //byte, line and column where the current token starts
+    diagnostics: Vec<Diagnostic>,
+    trivia: bool, //keep whitespace and comments as tokens
 }
 
 impl<'a> Scanner<'a> {
-    pub fn new(source: &'a str, filename: &'a str) -> Scanner<'a> {
+    pub fn new(source: &'a str) -> Scanner<'a> {
         Scanner {
             source,
-            chars: source.chars().peekable(),
+            current: 0,
             line: 1,
+            column: 1,
             tokens: Vec::new(),
-            filename,
-            had_error: false,
+            end: source.len(),
+            start: (0, 1, 1),
+            diagnostics: Vec::new(),
+            trivia: false,
         }
     }
 
-    pub fn scan_tokens(&mut self) -> Vec<Token> {
-        while let Some(ch) = self.chars.next() {
+    //keeps whitespace and comments as Whitespace and Comment tokens and leaves out the
+    //printables (they are inside their string), so the tokens cover every byte of the
+    //source. for tools that need to give back the exact file, the parser cant use these
+    pub fn lossless(source: &'a str) -> Scanner<'a> {
+        Scanner {
+            trivia: true,
+            ..Scanner::new(source)
+        }
+    }
+
+    //scans only the part of the source covered by `span` (like a printable inside a string)
+    pub fn nested(source: &'a str, span: Span) -> Scanner<'a> {
+        Scanner {
+            current: span.start,
+            line: span.line,
+            column: span.column,
+            end: span.end,
+            start: (span.start, span.line, span.column),
+            ..Scanner::new(source)
+        }
+    }
+
+    //returns the tokens along with everything that was wrong with them. bad input
+    //becomes an Error token so the parser can still run over the rest of the file
+    pub fn scan_tokens(&mut self) -> (Vec<Token>, Vec<Diagnostic>) {
+        loop {
+            self.start = (self.current, self.line, self.column);
+            let ch = match self.advance() {
+                Some(ch) => ch,
+                None => break,
+            };
             match ch {
-                '(' => self.make_token(TokenType::LeftParen, ch.to_string(), self.line, None),
-                ')' => self.make_token(TokenType::RightParen, ch.to_string(), self.line, None),
-                '{' => self.make_token(TokenType::LeftBrace, ch.to_string(), self.line, None),
-                '}' => self.make_token(TokenType::RightBrace, ch.to_string(), self.line, None),
-                ',' => self.make_token(TokenType::Comma, ch.to_string(), self.line, None),
-                '-' => {
-                    if self.tokens.len() >= 2
-                        && self.tokens[self.tokens.len() - 1].tt == TokenType::Minus
-                        && self.tokens[self.tokens.len() - 2].tt != TokenType::Int
-                        && self.tokens[self.tokens.len() - 2].tt != TokenType::Float
-                    {
-                        error::KlangError::error(
-                            KlangError::ScannerError,
-                            "we shall not allow minus spamming. use 1 bitch",
-                            self.line,
-                            self.filename,
+                '(' => self.make_token(TokenType::LeftParen, ch.to_string(), None),
+                ')' => self.make_token(TokenType::RightParen, ch.to_string(), None),
+                '{' => self.make_token(TokenType::LeftBrace, ch.to_string(), None),
+                '}' => self.make_token(TokenType::RightBrace, ch.to_string(), None),
+                ',' => self.make_token(TokenType::Comma, ch.to_string(), None),
+                '-' => self.or_double(
+                    ch,
+                    TokenType::Minus,
+                    TokenType::MinusEqual,
+                    TokenType::MinusMinus,
+                ),
+                '+' => self.or_double(
+                    ch,
+                    TokenType::Plus,
+                    TokenType::PlusEqual,
+                    TokenType::PlusPlus,
+                ),
+                ';' => self.make_token(TokenType::Semicolon, ch.to_string(), None),
+                '*' => self.or_equal(ch, TokenType::Star, TokenType::StarEqual),
+                '%' => self.or_equal(ch, TokenType::Modulo, TokenType::ModuloEqual),
+                '[' => self.make_token(TokenType::LeftSquare, ch.to_string(), None),
+                ':' => {
+                    if self.is_next(':') {
+                        self.advance();
+                        self.make_token(TokenType::DoubleColon, "::".to_string(), None);
+                    } else {
+                        self.make_token(TokenType::Colon, ch.to_string(), None)
+                    }
+                }
+                '\'' => {
+                    //a loop label, like 'outer
+                    if self.peek().is_some_and(|x| x == '_' || x.is_xid_start()) {
+                        let mut label = String::from(ch);
+                        while self.peek().unwrap_or('\0').is_xid_continue() {
+                            label.push(self.advance().unwrap());
+                        }
+                        self.make_token(TokenType::Label, label, None);
+                    } else {
+                        self.error_token(
+                            self.diagnostic("a label needs a name")
+                                .help("labels look like 'outer"),
                         );
-                        self.had_error = true;
                     }
-                    self.make_token(TokenType::Minus, ch.to_string(), self.line, None)
                 }
-                '+' => self.make_token(TokenType::Plus, ch.to_string(), self.line, None),
-                ';' => self.make_token(TokenType::Semicolon, ch.to_string(), self.line, None),
-                '*' => self.make_token(TokenType::Star, ch.to_string(), self.line, None),
-                '%' => self.make_token(TokenType::Modulo, ch.to_string(), self.line, None),
-                '[' => self.make_token(TokenType::LeftSquare, ch.to_string(), self.line, None),
-                ']' => self.make_token(TokenType::RightSquare, ch.to_string(), self.line, None),
+                ']' => self.make_token(TokenType::RightSquare, ch.to_string(), None),
                 '/' => {
                     if self.is_next('/') {
-                        while self.chars.next() != Some('\n') && self.chars.peek().is_some() {}
-                        self.line += 1;
+                        self.advance();
+                        if self.is_next('/') && self.peek_next() != Some('/') {
+                            self.doc_comment();
+                        } else {
+                            while self.peek().is_some() && !self.is_next('\n') {
+                                self.advance();
+                            }
+                            self.trivia_token(TokenType::Comment);
+                        }
+                    } else if self.is_next('*') {
+                        self.block_comment();
+                        self.trivia_token(TokenType::Comment);
                     } else {
-                        self.make_token(TokenType::Slash, ch.to_string(), self.line, None);
+                        self.or_equal(ch, TokenType::Slash, TokenType::SlashEqual);
                     }
                 }
                 '!' => {
                     if self.is_next('=') {
-                        let next = self.chars.next().unwrap();
+                        let next = self.advance().unwrap();
                         self.make_token(
                             TokenType::BangEqual,
                             String::from(ch) + &String::from(next),
-                            self.line,
                             None,
                         );
                     } else {
-                        if !self.tokens.is_empty()
-                            && self.tokens[self.tokens.len() - 1].tt == TokenType::Bang
-                        {
-                            error::KlangError::error(
-                                KlangError::ScannerError,
-                                "we shall not allow bang spamming. use 1 bitch",
-                                self.line,
-                                self.filename,
-                            );
-                            self.had_error = true;
-                        }
-                        self.make_token(TokenType::Bang, ch.to_string(), self.line, None)
+                        self.make_token(TokenType::Bang, ch.to_string(), None)
                     }
                 }
                 '=' => {
                     if self.is_next('=') {
-                        let next = self.chars.next().unwrap();
+                        let next = self.advance().unwrap();
                         self.make_token(
                             TokenType::EqualEqual,
                             String::from(ch) + &String::from(next),
-                            self.line,
                             None,
                         );
                     } else {
-                        self.make_token(TokenType::Equal, ch.to_string(), self.line, None)
+                        self.make_token(TokenType::Equal, ch.to_string(), None)
                     }
                 }
                 '>' => {
                     if self.is_next('=') {
-                        let next = self.chars.next().unwrap();
+                        let next = self.advance().unwrap();
                         self.make_token(
                             TokenType::GreaterEqual,
                             String::from(ch) + &String::from(next),
-                            self.line,
                             None,
                         );
                     } else {
-                        self.make_token(TokenType::Greater, ch.to_string(), self.line, None)
+                        self.make_token(TokenType::Greater, ch.to_string(), None)
                     }
                 }
                 '<' => {
                     if self.is_next('=') {
-                        let next = self.chars.next().unwrap();
+                        let next = self.advance().unwrap();
                         self.make_token(
                             TokenType::LessEqual,
                             String::from(ch) + &String::from(next),
-                            self.line,
                             None,
                         );
                     } else {
-                        self.make_token(TokenType::Less, ch.to_string(), self.line, None)
+                        self.make_token(TokenType::Less, ch.to_string(), None)
                     }
                 }
                 '.' => {
                     if self.is_next('.') {
-                        let next = self.chars.next().unwrap();
+                        let next = self.advance().unwrap();
                         self.make_token(
                             TokenType::Range,
                             String::from(ch) + &String::from(next),
-                            self.line,
                             None,
                         );
                     } else {
-                        self.make_token(TokenType::Dot, ch.to_string(), self.line, None)
+                        self.make_token(TokenType::Dot, ch.to_string(), None)
                     }
                 }
                 '&' => {
                     if self.is_next('&') {
-                        let next = self.chars.next().unwrap();
+                        let next = self.advance().unwrap();
                         self.make_token(
                             TokenType::And,
                             String::from(ch) + &String::from(next),
-                            self.line,
                             None,
                         )
                     } else {
-                        error::KlangError::error(
-                            KlangError::ScannerError,
-                            "missing a second & you fat fuck",
-                            self.line,
-                            self.filename,
+                        self.error_token(
+                            self.diagnostic("missing a second & you fat fuck")
+                                .help("use && for a logical and"),
                         );
-                        self.had_error = true;
                     }
                 }
                 '|' => {
                     if self.is_next('|') {
-                        let next = self.chars.next().unwrap();
-                        self.make_token(
-                            TokenType::Or,
-                            String::from(ch) + &String::from(next),
-                            self.line,
-                            None,
-                        )
+                        let next = self.advance().unwrap();
+                        self.make_token(TokenType::Or, String::from(ch) + &String::from(next), None)
                     } else {
-                        error::KlangError::error(
-                            KlangError::ScannerError,
-                            "missing a second | you stupid gay",
-                            self.line,
-                            self.filename,
+                        self.error_token(
+                            self.diagnostic("missing a second | you stupid gay")
+                                .help("use || for a logical or"),
                         );
-                        self.had_error = true;
                     }
                 }
-                '"' => self.string(),
-                ' ' => (),
-                '\r' => (),
-                '\t' => (),
-                '\n' => self.line += 1,
+                '"' => {
+                    if self.is_next('"') && self.peek_next() == Some('"') {
+                        self.multiline_string();
+                    } else {
+                        self.string();
+                    }
+                }
+                'r' if self.is_next('"') || self.is_next('#') => self.raw_string(),
+                ' ' | '\r' | '\t' | '\n' => {
+                    while matches!(self.peek(), Some(' ' | '\r' | '\t' | '\n')) {
+                        self.advance();
+                    }
+                    self.trivia_token(TokenType::Whitespace);
+                }
                 _ => {
                     if ch.is_ascii_digit() {
                         self.number(ch);
-                    } else if ch.is_ascii_alphabetic() {
+                    } else if ch == '_' || ch.is_xid_start() {
                         self.identifier(ch);
                     } else {
-                        error::KlangError::error(
-                            KlangError::ScannerError,
-                            "unexpected character",
-                            self.line,
-                            self.filename,
-                        );
-                        self.had_error = true;
+                        self.error_token(self.diagnostic("unexpected character"));
                     }
                 }
             }
         }
-        self.make_token(TokenType::Eof, String::from(""), self.line, None);
-        if self.had_error {
-            std::process::exit(0);
-        } else {
-            std::mem::take(&mut self.tokens)
+        self.start = (self.current, self.line, self.column);
+        if !self.trivia {
+            //errors at the end of the file point right after the last thing in it,
+            //not at the empty line after the last newline
+            let end = self.source[..self.current].trim_end().len();
+            let line_start = self.source[..end].rfind('\n').map_or(0, |i| i + 1);
+            let skipped = self.source[end..self.current].matches('\n').count();
+            self.start = (
+                end,
+                self.line - skipped,
+                self.source[line_start..end].chars().count() + 1,
+            );
+            (self.current, self.line, self.column) = self.start;
         }
+        self.make_token(TokenType::Eof, String::from(""), None);
+        (
+            std::mem::take(&mut self.tokens),
+            std::mem::take(&mut self.diagnostics),
+        )
     }
-    fn make_token(&mut self, tt: TokenType, text: String, line: usize, value: Option<Value>) {
+    fn make_token(&mut self, tt: TokenType, text: String, value: Option<Value>) {
+        let span = self.span();
         self.tokens.push(Token {
             tt,
             lexeme: text,
             literal: value,
-            line,
+            span,
         })
     }
+    fn span(&self) -> Span {
+        //the span from the start of the current token up to the current position
+        self.span_from(self.start)
+    }
+    fn span_from(&self, start: (usize, usize, usize)) -> Span {
+        Span {
+            start: start.0,
+            end: self.current,
+            line: start.1,
+            column: start.2,
+            end_line: self.line,
+            end_column: self.column,
+        }
+    }
+    fn advance(&mut self) -> Option<char> {
+        let ch = self.peek()?;
+        self.current += ch.len_utf8();
+        if ch == '\n' {
+            self.line += 1;
+            self.column = 1;
+        } else {
+            self.column += 1;
+        }
+        Some(ch)
+    }
+    fn peek(&self) -> Option<char> {
+        self.source[self.current..self.end].chars().next()
+    }
+    fn peek_next(&self) -> Option<char> {
+        self.source[self.current..self.end].chars().nth(1)
+    }
+    fn diagnostic(&self, msg: &str) -> Diagnostic {
+        Diagnostic::new(ErrorKind::ScannerError, msg, self.span())
+    }
+    fn error(&mut self, diagnostic: Diagnostic) {
+        self.diagnostics.push(diagnostic);
+    }
+    fn trivia_token(&mut self, tt: TokenType) {
+        if self.trivia {
+            let text = self.source[self.start.0..self.current].to_string();
+            self.make_token(tt, text, None);
+        }
+    }
+    fn error_token(&mut self, diagnostic: Diagnostic) {
+        //the bad input turns into an Error token, the parser knows it was already reported
+        self.error(diagnostic);
+        let text = self.source[self.start.0..self.current].to_string();
+        self.make_token(TokenType::Error, text, None);
+    }
     fn is_next(&mut self, ch: char) -> bool {
-        self.chars.peek() == Some(&ch)
+        self.peek() == Some(ch)
+    }
+    fn or_double(&mut self, ch: char, tt: TokenType, with_equal: TokenType, double: TokenType) {
+        //+ or += or ++
+        if self.is_next(ch) {
+            self.advance();
+            self.make_token(double, format!("{ch}{ch}"), None);
+        } else {
+            self.or_equal(ch, tt, with_equal);
+        }
+    }
+    fn or_equal(&mut self, ch: char, tt: TokenType, with_equal: TokenType) {
+        //+ or +=
+        if self.is_next('=') {
+            self.advance();
+            self.make_token(with_equal, format!("{ch}="), None);
+        } else {
+            self.make_token(tt, ch.to_string(), None);
+        }
     }
 
     fn identifier(&mut self, ch: char) {
         let mut word = String::from(ch);
-        while self.chars.peek().unwrap_or(&'\0').is_ascii_alphanumeric() {
-            word.push(self.chars.next().unwrap());
+        while self.peek().unwrap_or('\0').is_xid_continue() {
+            word.push(self.advance().unwrap());
         }
         match word.as_str() {
-            "let" => self.make_token(TokenType::Let, "".to_string(), self.line, None),
-            "in" => self.make_token(TokenType::In, "".to_string(), self.line, None),
-            "else" => self.make_token(TokenType::Else, "".to_string(), self.line, None),
-            "for" => self.make_token(TokenType::For, "".to_string(), self.line, None),
-            "if" => self.make_token(TokenType::If, "".to_string(), self.line, None),
-            "print" => self.make_token(TokenType::Print, "".to_string(), self.line, None),
-            "while" => self.make_token(TokenType::While, "".to_string(), self.line, None),
-            "int" => self.make_token(TokenType::Int, "".to_string(), self.line, None),
-            "float" => self.make_token(TokenType::Float, "".to_string(), self.line, None),
-            "string" => self.make_token(TokenType::String, "".to_string(), self.line, None),
-            "bool" => self.make_token(TokenType::Bool, "".to_string(), self.line, None),
-            "fn" => self.make_token(TokenType::Fn, "".to_string(), self.line, None),
-            "return" => self.make_token(TokenType::Return, "".to_string(), self.line, None),
-            "true" => self.make_token(
-                TokenType::Bool,
-                "true".to_string(),
-                self.line,
-                Some(Value::Bool(true)),
-            ),
+            "let" => self.make_token(TokenType::Let, "".to_string(), None),
+            "in" => self.make_token(TokenType::In, "".to_string(), None),
+            "else" => self.make_token(TokenType::Else, "".to_string(), None),
+            "for" => self.make_token(TokenType::For, "".to_string(), None),
+            "if" => self.make_token(TokenType::If, "".to_string(), None),
+            "print" => self.make_token(TokenType::Print, "".to_string(), None),
+            "while" => self.make_token(TokenType::While, "".to_string(), None),
+            "int" => self.make_token(TokenType::Int, "".to_string(), None),
+            "float" => self.make_token(TokenType::Float, "".to_string(), None),
+            "string" => self.make_token(TokenType::String, "".to_string(), None),
+            "bool" => self.make_token(TokenType::Bool, "".to_string(), None),
+            "fn" => self.make_token(TokenType::Fn, "".to_string(), None),
+            "struct" => self.make_token(TokenType::Struct, "".to_string(), None),
+            "impl" => self.make_token(TokenType::Impl, "".to_string(), None),
+            "return" => self.make_token(TokenType::Return, "".to_string(), None),
+            "break" => self.make_token(TokenType::Break, "".to_string(), None),
+            "continue" => self.make_token(TokenType::Continue, "".to_string(), None),
+            "true" => self.make_token(TokenType::Bool, "true".to_string(), Some(Value::Bool(true))),
             "false" => self.make_token(
                 TokenType::Bool,
                 "false".to_string(),
-                self.line,
                 Some(Value::Bool(false)),
             ),
             "std" => {
-                if self.chars.next().unwrap() == ':' && self.chars.next().unwrap() == ':' {
-                    self.make_token(TokenType::NativeCall, "".to_string(), self.line, None)
+                if self.advance() == Some(':') && self.advance() == Some(':') {
+                    self.make_token(TokenType::NativeCall, "".to_string(), None)
                 } else {
-                    error::KlangError::error(
-                        KlangError::ScannerError,
-                        "cannot use std without calling a native fn",
-                        self.line,
-                        self.filename,
+                    self.error_token(
+                        self.diagnostic("cannot use std without calling a native fn")
+                            .help("native functions are called like std::sqrt(2)"),
                     );
-                    self.had_error = true;
                 }
             }
-            _ => self.make_token(TokenType::Identifier, word, self.line, None),
+            _ => self.make_token(TokenType::Identifier, word, None),
         }
     }
     fn number(&mut self, ch: char) {
+        if ch == '0' {
+            let radix = match self.peek() {
+                Some('x') => Some((16, "hex")),
+                Some('b') => Some((2, "binary")),
+                Some('o') => Some((8, "octal")),
+                _ => None,
+            };
+            if let Some((radix, name)) = radix {
+                self.advance();
+                return self.radix_number(radix, name);
+            }
+        }
+        let errors = self.diagnostics.len();
         let mut number = String::from(ch);
-        while self.chars.peek().unwrap_or(&'\0').is_ascii_digit() {
-            number.push(self.chars.next().unwrap());
-        }
-        if self.chars.peek().unwrap_or(&'\0') == &'.' {
-            number.push(self.chars.next().unwrap());
-            if self.chars.peek().unwrap_or(&'\0') == &'.' {
-                number.pop();
-                let value = match number.parse::<i64>() {
-                    Ok(e) => Some(Value::Number(e as f64)),
-                    Err(_) => {
-                        error::KlangError::error(
-                            KlangError::ScannerError,
-                            "failed to parse integer",
-                            self.line,
-                            self.filename,
-                        );
-                        self.had_error = true;
-                        Some(Value::Number(0.0))
-                    }
-                };
-                self.make_token(TokenType::Int, "".to_string(), self.line, value);
-                self.make_token(TokenType::Range, "..".to_string(), self.line, None);
-                self.chars.next(); //consume 2nd dot
+        self.digits(&mut number);
+        let mut float = false;
+        if self.peek() == Some('.') && self.peek_next() != Some('.') {
+            float = true;
+            number.push(self.advance().unwrap());
+            self.digits(&mut number);
+            if number.ends_with('.') {
+                self.error(
+                    self.diagnostic("float cant end with a dot")
+                        .help("add a digit after the dot, like 1.0"),
+                );
+            }
+        }
+        if matches!(self.peek(), Some('e' | 'E')) {
+            float = true;
+            number.push(self.advance().unwrap());
+            if matches!(self.peek(), Some('+' | '-')) {
+                number.push(self.advance().unwrap());
+            }
+            let before = number.len();
+            self.digits(&mut number);
+            if number.len() == before {
+                self.error(
+                    self.diagnostic("missing the exponent after the e")
+                        .help("scientific notation looks like 1.5e-3"),
+                );
+            }
+        }
+        if number
+            .split(['.', 'e', 'E', '+', '-'])
+            .any(|part| part.starts_with('_') || part.ends_with('_') || part.contains("__"))
+        {
+            self.error(
+                self.diagnostic("_ can only go between digits")
+                    .help("write it like 1_000_000"),
+            );
+        }
+        let number = number.replace('_', "");
+        if self.diagnostics.len() > errors {
+            //already complained about this number
+            let tt = if float {
+                TokenType::Float
             } else {
-                while self.chars.peek().unwrap_or(&'\0').is_ascii_digit() {
-                    number.push(self.chars.next().unwrap());
-                }
-                if number.ends_with('.') {
-                    error::KlangError::error(
-                        KlangError::ScannerError,
-                        "float cant end with a dot",
-                        self.line,
-                        self.filename,
-                    );
-                    self.had_error = true;
+                TokenType::Int
+            };
+            self.make_token(tt, "".to_string(), Some(Value::Number(0.0)))
+        } else if float {
+            let value = match number.parse::<f64>() {
+                Ok(e) => Some(Value::Number(e)),
+                Err(_) => {
+                    self.error(self.diagnostic("failed to parse float"));
+                    Some(Value::Number(0.0))
                 }
-                let value = match number.parse::<f64>() {
-                    Ok(e) => Some(Value::Number(e)),
-                    Err(_) => {
-                        error::KlangError::error(
-                            KlangError::ScannerError,
-                            "failed to parse float",
-                            self.line,
-                            self.filename,
-                        );
-                        self.had_error = true;
-                        Some(Value::Number(0.0))
-                    }
-                };
-                self.make_token(TokenType::Float, "".to_string(), self.line, value)
-            }
+            };
+            self.make_token(TokenType::Float, "".to_string(), value)
         } else {
             let value = match number.parse::<i64>() {
                 Ok(e) => Some(Value::Number(e as f64)),
                 Err(_) => {
-                    error::KlangError::error(
-                        KlangError::ScannerError,
-                        "failed to parse integer",
-                        self.line,
-                        self.filename,
-                    );
-                    self.had_error = true;
+                    self.error(self.diagnostic("failed to parse integer"));
                     Some(Value::Number(0.0))
                 }
             };
-            self.make_token(TokenType::Int, "".to_string(), self.line, value)
+            self.make_token(TokenType::Int, "".to_string(), value)
+        }
+    }
+    fn digits(&mut self, number: &mut String) {
+        while matches!(self.peek(), Some(c) if c.is_ascii_digit() || c == '_') {
+            number.push(self.advance().unwrap());
         }
     }
+    fn radix_number(&mut self, radix: u32, name: &str) {
+        //0x, 0b and 0o numbers. letters are read too so 0b102 is one bad number and not 0b10 2
+        let prefix = self.source[self.start.0..self.current].to_string();
+        let mut digits = String::new();
+        while matches!(self.peek(), Some(c) if c.is_ascii_alphanumeric() || c == '_') {
+            digits.push(self.advance().unwrap());
+        }
+        let value = if digits.is_empty() {
+            self.error(
+                self.diagnostic(&format!("missing digits after {prefix}"))
+                    .help("write it like 0xFF, 0b1010 or 0o17"),
+            );
+            0
+        } else if let Some(bad) = digits.chars().find(|&c| c != '_' && !c.is_digit(radix)) {
+            self.error(self.diagnostic(&format!("{bad} is not a valid {name} digit")));
+            0
+        } else if digits.starts_with('_') || digits.ends_with('_') || digits.contains("__") {
+            self.error(
+                self.diagnostic("_ can only go between digits")
+                    .help("write it like 0xFF_FF"),
+            );
+            0
+        } else {
+            match i64::from_str_radix(&digits.replace('_', ""), radix) {
+                Ok(value) => value,
+                Err(_) => {
+                    self.error(self.diagnostic(&format!("{name} number is too big")));
+                    0
+                }
+            }
+        };
+        self.make_token(
+            TokenType::Int,
+            "".to_string(),
+            Some(Value::Number(value as f64)),
+        )
+    }
     fn string(&mut self) {
         let mut printables: Vec<Token> = Vec::new();
         let mut string = String::new();
-        while self.chars.peek().unwrap_or(&'\0') != &'"' {
-            if self.chars.peek().unwrap_or(&'\0') == &'\n' {
-                self.line += 1
-            }
-            if self.chars.peek().is_none() {
-                error::KlangError::error(
-                    KlangError::ScannerError,
-                    "unterminated string",
-                    self.line,
-                    self.filename,
+        while self.peek() != Some('"') {
+            if self.peek().is_none() {
+                return self.error_token(
+                    self.diagnostic("unterminated string")
+                        .note("strings must be closed with a \" before the end of the file"),
                 );
-                self.had_error = true;
-                break;
             } else {
-                match self.chars.peek().unwrap() {
+                match self.peek().unwrap() {
                     '{' => {
-                        string.push(self.chars.next().unwrap());
-                        let mut string1 = String::new();
-                        if self.chars.peek() == Some(&'}') {
-                            error::KlangError::error(
-                                KlangError::ScannerError,
-                                "cannot print an empty identifier",
-                                self.line,
-                                self.filename,
+                        let brace = (self.current, self.line, self.column);
+                        self.advance();
+                        if self.is_next('}') {
+                            self.advance();
+                            self.error(
+                                Diagnostic::new(
+                                    ErrorKind::ScannerError,
+                                    "cannot print an empty identifier",
+                                    self.span_from(brace),
+                                )
+                                .help("put an expression between the braces, like {x}, or write \\{\\} for the braces themselves"),
                             );
-                            self.had_error = true;
+                            continue;
                         }
+                        string.push('{');
+                        let mut string1 = String::new();
+                        let start = (self.current, self.line, self.column);
                         let mut counter = 1;
-                        while self.chars.peek().is_some() {
-                            let ch1 = self.chars.next().unwrap();
+                        while self.peek().is_some() {
+                            let ch1 = self.advance().unwrap();
                             string1.push(ch1);
-                            match self.chars.peek().unwrap() {
+                            match self.peek().unwrap_or('\0') {
                                 '{' => counter += 1,
                                 '}' => counter -= 1,
                                 _ => (),
@@ -382,20 +551,232 @@ impl<'a> Scanner<'a> {
                                     tt: TokenType::Printable,
                                     lexeme: string1,
                                     literal: None,
-                                    line: self.line,
+                                    span: self.span_from(start),
                                 });
+                                string.push(self.advance().unwrap()); //the closing }
                                 break;
                             }
                         }
                     }
-                    _ => string.push(self.chars.next().unwrap()),
+                    '}' => {
+                        self.advance();
+                        string.push_str("}}");
+                    }
+                    '\\' => self.escape(&mut string),
+                    _ => string.push(self.advance().unwrap()),
                 }
             }
         }
-        self.chars.next(); //consume the 2nd "
-        self.make_token(TokenType::String, string, self.line, None);
-        for i in printables.into_iter() {
-            self.tokens.push(i);
+        self.advance(); //consume the 2nd "
+        self.make_token(TokenType::String, string, None);
+        if !self.trivia {
+            self.tokens.extend(printables);
+        }
+    }
+    fn raw_string(&mut self) {
+        //r"..." or r#"..."# takes everything as it is, no escapes and no printables.
+        //the #s let the string have " inside of it
+        let mut hashes = 0;
+        while self.is_next('#') {
+            self.advance();
+            hashes += 1;
+        }
+        if !self.is_next('"') {
+            return self.error_token(
+                self.diagnostic("missing \" after r#")
+                    .help("raw strings look like r\"...\" or r#\"...\"#"),
+            );
+        }
+        self.advance();
+        let closing = format!("\"{}", "#".repeat(hashes));
+        let start = self.current;
+        while !self.source[self.current..self.end].starts_with(&closing) {
+            if self.advance().is_none() {
+                return self.error_token(
+                    self.diagnostic("unterminated raw string")
+                        .note(&format!("raw strings must be closed with {closing}")),
+                );
+            }
+        }
+        let text = self.source[start..self.current].to_string();
+        for _ in closing.chars() {
+            self.advance();
+        }
+        self.literal_string(&text);
+    }
+    fn multiline_string(&mut self) {
+        //"""...""" strings are raw too, and lose the indentation they have in the code:
+        //the newline after the opening """ and the line of the closing """ are dropped,
+        //and the indentation all the lines share is removed from every line
+        self.advance();
+        self.advance();
+        let start = self.current;
+        while !self.source[self.current..self.end].starts_with("\"\"\"") {
+            if self.advance().is_none() {
+                return self.error_token(
+                    self.diagnostic("unterminated string")
+                        .note("multi-line strings must be closed with \"\"\""),
+                );
+            }
+        }
+        let text = self.source[start..self.current].to_string();
+        for _ in 0..3 {
+            self.advance();
+        }
+        let mut lines: Vec<&str> = text.split('\n').map(|x| x.trim_end_matches('\r')).collect();
+        if lines.len() > 1 && lines[0].trim().is_empty() {
+            lines.remove(0);
+        }
+        let closing = if lines.len() > 1 && lines[lines.len() - 1].trim().is_empty() {
+            lines.pop()
+        } else {
+            None
+        };
+        //the indentation every line starts with, compared character by character so
+        //tabs and spaces (or any other whitespace) are never mixed up
+        let strip = lines
+            .iter()
+            .filter(|line| !line.trim().is_empty())
+            .copied()
+            .chain(closing)
+            .map(|line| &line[..line.len() - line.trim_start().len()])
+            .reduce(common_prefix)
+            .unwrap_or("");
+        let text: Vec<&str> = lines
+            .iter()
+            .map(|line| line.strip_prefix(strip).unwrap_or(line.trim_start()))
+            .collect();
+        self.literal_string(&text.join("\n"));
+    }
+    fn literal_string(&mut self, text: &str) {
+        //a string without printables, its braces are doubled so print shows them as they are
+        let mut string = String::with_capacity(text.len());
+        for ch in text.chars() {
+            push_literal(&mut string, ch);
+        }
+        self.make_token(TokenType::String, string, None);
+    }
+    fn doc_comment(&mut self) {
+        //a /// comment documents the fn or let right after it, so it becomes a token
+        self.advance();
+        let mut text = String::new();
+        while self.peek().is_some() && !self.is_next('\n') {
+            text.push(self.advance().unwrap());
+        }
+        let text = text
+            .strip_prefix(' ')
+            .unwrap_or(&text)
+            .trim_end()
+            .to_string();
+        self.make_token(TokenType::DocComment, text, None);
+    }
+    fn block_comment(&mut self) {
+        //block comments nest, so /* a /* b */ c */ is one comment
+        self.advance();
+        let opener = self.span();
+        let mut depth = 1;
+        while depth > 0 {
+            match self.advance() {
+                Some('/') if self.is_next('*') => {
+                    self.advance();
+                    depth += 1;
+                }
+                Some('*') if self.is_next('/') => {
+                    self.advance();
+                    depth -= 1;
+                }
+                Some(_) => (),
+                None => {
+                    self.error(
+                        Diagnostic::new(
+                            ErrorKind::ScannerError,
+                            "unterminated block comment",
+                            opener,
+                        )
+                        .note("every /* needs a */ to close it, even the nested ones"),
+                    );
+                    return;
+                }
+            }
+        }
+    }
+    fn escape(&mut self, string: &mut String) {
+        let start = (self.current, self.line, self.column);
+        self.advance(); //the \
+        match self.advance() {
+            Some('n') => string.push('\n'),
+            Some('t') => string.push('\t'),
+            Some('\\') => string.push('\\'),
+            Some('"') => string.push('"'),
+            Some('{') => string.push_str("{{"),
+            Some('}') => string.push_str("}}"),
+            Some('u') => {
+                if let Some(ch) = self.unicode_escape(start) {
+                    push_literal(string, ch);
+                }
+            }
+            Some(ch) => self.error(
+                Diagnostic::new(
+                    ErrorKind::ScannerError,
+                    &format!("unknown escape \\{ch}"),
+                    self.span_from(start),
+                )
+                .help("the escapes are \\n \\t \\\\ \\\" \\{ \\} and \\u{XXXX}"),
+            ),
+            None => (), //the string is unterminated, thats reported by string()
+        }
+    }
+    fn unicode_escape(&mut self, start: (usize, usize, usize)) -> Option<char> {
+        //\u{XXXX} with 1 to 6 hex digits
+        if !self.is_next('{') {
+            self.error(
+                Diagnostic::new(
+                    ErrorKind::ScannerError,
+                    "missing { after \\u",
+                    self.span_from(start),
+                )
+                .help("unicode escapes look like \\u{1F600}"),
+            );
+            return None;
+        }
+        self.advance();
+        let mut digits = String::new();
+        while self.peek().unwrap_or('\0').is_ascii_hexdigit() {
+            digits.push(self.advance().unwrap());
+        }
+        if !self.is_next('}') {
+            self.error(
+                Diagnostic::new(
+                    ErrorKind::ScannerError,
+                    "unterminated unicode escape",
+                    self.span_from(start),
+                )
+                .help("close it with a }, like \\u{1F600}"),
+            );
+            return None;
+        }
+        self.advance();
+        if digits.is_empty() || digits.len() > 6 {
+            self.error(
+                Diagnostic::new(
+                    ErrorKind::ScannerError,
+                    "unicode escape must have 1 to 6 hex digits",
+                    self.span_from(start),
+                )
+                .help("unicode escapes look like \\u{1F600}"),
+            );
+            return None;
+        }
+        match char::from_u32(u32::from_str_radix(&digits, 16).unwrap()) {
+            Some(ch) => Some(ch),
+            None => {
+                self.error(Diagnostic::new(
+                    ErrorKind::ScannerError,
+                    &format!("{digits} is not a valid unicode character"),
+                    self.span_from(start),
+                ));
+                None
+            }
         }
     }
 }
@@ -413,6 +794,13 @@ pub enum TokenType {
     Slash,
     Star,
     Modulo,
+    PlusEqual,
+    MinusEqual,
+    StarEqual,
+    SlashEqual,
+    ModuloEqual,
+    PlusPlus,
+    MinusMinus,
     Semicolon,
     LeftSquare,
     RightSquare,
@@ -442,9 +830,20 @@ pub enum TokenType {
     While,
     Print,
     Fn,
+    Struct,
+    Impl,
     Return,
+    Break,
+    Continue,
+    Label,
+    Colon,
+    DoubleColon,
     Printable,
     NativeCall,
+    DocComment,
+    Comment,
+    Whitespace,
+    Error,
     Eof,
 }
 
@@ -464,6 +863,13 @@ impl fmt::Display for TokenType {
             TokenType::Slash => write!(f, "Slash"),
             TokenType::Star => write!(f, "Star"),
             TokenType::Modulo => write!(f, "Modulo"),
+            TokenType::PlusEqual => write!(f, "PlusEqual"),
+            TokenType::MinusEqual => write!(f, "MinusEqual"),
+            TokenType::StarEqual => write!(f, "StarEqual"),
+            TokenType::SlashEqual => write!(f, "SlashEqual"),
+            TokenType::ModuloEqual => write!(f, "ModuloEqual"),
+            TokenType::PlusPlus => write!(f, "PlusPlus"),
+            TokenType::MinusMinus => write!(f, "MinusMinus"),
             TokenType::Semicolon => write!(f, "Semicolon"),
             TokenType::Bang => write!(f, "Bang"),
             TokenType::BangEqual => write!(f, "BangEqual"),
@@ -489,10 +895,21 @@ impl fmt::Display for TokenType {
             TokenType::While => write!(f, "While"),
             TokenType::Print => write!(f, "Print"),
             TokenType::Fn => write!(f, "function"),
+            TokenType::Struct => write!(f, "struct"),
+            TokenType::Impl => write!(f, "impl"),
             TokenType::Return => write!(f, "return"),
+            TokenType::Break => write!(f, "break"),
+            TokenType::Continue => write!(f, "continue"),
+            TokenType::Label => write!(f, "Label"),
+            TokenType::Colon => write!(f, "Colon"),
+            TokenType::DoubleColon => write!(f, "DoubleColon"),
             TokenType::Eof => write!(f, "Eof"),
             TokenType::Printable => write!(f, "Printable"),
             TokenType::NativeCall => write!(f, "NativeCall"),
+            TokenType::DocComment => write!(f, "DocComment"),
+            TokenType::Comment => write!(f, "Comment"),
+            TokenType::Whitespace => write!(f, "Whitespace"),
+            TokenType::Error => write!(f, "Error"),
         }
     }
 }
@@ -506,9 +923,140 @@ pub enum Value {
     Number(f64),
     Bool(bool),
     Vec(Vec<Value>),
+    Map(BTreeMap<MapKey, Value>),
+    Function(Function),
+    Type(Rc<StructType>), // what struct Point { x, y } makes
+    Struct(Instance),     // Point { x: 1, y: 2 }
     None,
 }
 
+//what can be a key in a map, kept sorted so maps always print and loop in the same order
+#[derive(Debug, Clone, PartialEq)]
+pub enum MapKey {
+    Bool(bool),
+    Number(f64),
+    String(String),
+}
+
+impl MapKey {
+    pub fn new(value: &Value) -> Result<MapKey, String> {
+        match value {
+            Value::Bool(x) => Ok(MapKey::Bool(*x)),
+            Value::Number(x) if x.is_nan() => Err("cant use NaN as a map key".to_string()),
+            Value::Number(x) => Ok(MapKey::Number(x + 0.0)), //-0 and 0 are the same key
+            Value::String { string, .. } => Ok(MapKey::String(string.clone())),
+            x => Err(format!(
+                "cant use {x} as a map key, only strings, numbers and bools"
+            )),
+        }
+    }
+    pub fn value(&self) -> Value {
+        match self {
+            MapKey::Bool(x) => Value::Bool(*x),
+            MapKey::Number(x) => Value::Number(*x),
+            MapKey::String(x) => Value::String {
+                string: x.clone(),
+                printables: Vec::new(),
+            },
+        }
+    }
+}
+
+impl Eq for MapKey {}
+
+impl PartialOrd for MapKey {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl Ord for MapKey {
+    fn cmp(&self, other: &Self) -> Ordering {
+        match (self, other) {
+            (MapKey::Bool(x), MapKey::Bool(y)) => x.cmp(y),
+            (MapKey::Number(x), MapKey::Number(y)) => x.total_cmp(y),
+            (MapKey::String(x), MapKey::String(y)) => x.cmp(y),
+            //bools, then numbers, then strings
+            (MapKey::Bool(_), _) | (MapKey::Number(_), MapKey::String(_)) => Ordering::Less,
+            _ => Ordering::Greater,
+        }
+    }
+}
+
+impl fmt::Display for MapKey {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.value())
+    }
+}
+
+//one variable, shared with the closures that use it so they see (and change) the real thing.
+//its empty when a closure made it before the let that gives it a value
+pub type Slot = Rc<RefCell<Option<Value>>>;
+//the variables of one scope
+pub type Env = Rc<RefCell<HashMap<String, Slot>>>;
+
+#[derive(Clone)]
+pub struct Function {
+    pub name: Option<String>, //lambdas dont have one
+    pub params: Vec<String>,
+    pub start: usize, //where the body starts in the chunk
+    //only the variables the body uses, holding the whole scopes would keep them alive forever
+    //once the function is stored in one of them
+    pub captured: Rc<[(String, Slot)]>,
+}
+
+impl fmt::Debug for Function {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        //not the captured variables, a function can be in one of them
+        write!(f, "Function({self})")
+    }
+}
+
+impl fmt::Display for Function {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match &self.name {
+            Some(name) => write!(f, "<fn {name}>"),
+            None => write!(f, "<fn>"),
+        }
+    }
+}
+
+#[derive(Debug)]
+pub struct StructType {
+    pub name: String,
+    pub fields: Vec<String>,
+    pub methods: RefCell<HashMap<String, Function>>, //impl blocks add to it when they run
+}
+
+#[derive(Debug, Clone)]
+pub struct Instance {
+    pub kind: Rc<StructType>,
+    pub fields: Vec<Value>, //in the order the struct declares them
+}
+
+impl Instance {
+    pub fn field(&self, name: &str) -> Option<usize> {
+        self.kind.fields.iter().position(|field| field == name)
+    }
+}
+
+impl fmt::Display for Instance {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{} {{", self.kind.name)?;
+        for (i, (name, value)) in self.kind.fields.iter().zip(&self.fields).enumerate() {
+            if i > 0 {
+                write!(f, ",")?;
+            }
+            write!(f, " {name}: {value}")?;
+        }
+        if self.fields.is_empty() {
+            write!(f, "}}")
+        } else {
+            write!(f, " }}")
+        }
+    }
+}
+
 impl fmt::Display for Value {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
         match self {
@@ -540,25 +1088,116 @@ impl fmt::Display for Value {
                 };
                 write!(f, "]")
             }
+            Value::Map(map) => {
+                write!(f, "{{")?;
+                for (i, (key, value)) in map.iter().enumerate() {
+                    if i > 0 {
+                        write!(f, ", ")?;
+                    }
+                    write!(f, "{key}: {value}")?;
+                }
+                write!(f, "}}")
+            }
+            Value::Function(function) => write!(f, "{function}"),
+            Value::Type(kind) => write!(f, "<struct {}>", kind.name),
+            Value::Struct(instance) => write!(f, "{instance}"),
             Value::None => write!(f, "nada"),
         }
     }
 }
 
+//the longest start a and b have in common
+fn common_prefix<'a>(a: &'a str, b: &str) -> &'a str {
+    let len = a
+        .char_indices()
+        .zip(b.chars())
+        .take_while(|((_, x), y)| x == y)
+        .last()
+        .map_or(0, |((i, x), _)| i + x.len_utf8());
+    &a[..len]
+}
+
+//string literals are templates: every printable leaves a {} hole, and braces that
+//should be printed as they are get doubled ({{ and }}) so they dont look like holes
+fn push_literal(string: &mut String, ch: char) {
+    match ch {
+        '{' => string.push_str("{{"),
+        '}' => string.push_str("}}"),
+        _ => string.push(ch),
+    }
+}
+
+//fills the holes of a template from left to right and undoubles the braces
+pub fn fill_holes(template: &str, values: &[String]) -> String {
+    let mut string = String::with_capacity(template.len());
+    let mut values = values.iter();
+    let mut chars = template.chars().peekable();
+    while let Some(ch) = chars.next() {
+        match (ch, chars.peek()) {
+            ('{', Some('{')) | ('}', Some('}')) => {
+                chars.next();
+                string.push(ch);
+            }
+            ('{', Some('}')) => {
+                chars.next();
+                string.push_str(values.next().map(|x| x.as_str()).unwrap_or(""));
+            }
+            _ => string.push(ch),
+        }
+    }
+    string
+}
+
 #[derive(Debug, Clone)]
 pub struct Token {
     pub tt: TokenType,
     pub lexeme: String,
     pub literal: Option<Value>,
-    pub line: usize,
+    pub span: Span,
 }
 
 impl fmt::Display for Token {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
         write!(
             f,
-            "Token: {}, Lexeme: {}, Literal: {:?}, Line: {}",
-            self.tt, self.lexeme, self.literal, self.line
+            "Token: {}, Lexeme: {}, Literal: {:?}, Span: {}",
+            self.tt, self.lexeme, self.literal, self.span
+        )
+    }
+}
+
+//a region of the source code. start and end are byte offsets (end is exclusive),
+//lines and columns start from 1 and end_column points right after the last character
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub struct Span {
+    pub start: usize,
+    pub end: usize,
+    pub line: usize,
+    pub column: usize,
+    pub end_line: usize,
+    pub end_column: usize,
+}
+
+impl Span {
+    //the span covering both self and other (other should come after self)
+    pub fn to(self, other: Span) -> Span {
+        Span {
+            start: self.start,
+            end: other.end,
+            line: self.line,
+            column: self.column,
+            end_line: other.end_line,
+            end_column: other.end_column,
+        }
+    }
+}
+
+impl fmt::Display for Span {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "{}:{}-{}:{}",
+            self.line, self.column, self.end_line, self.end_column
         )
     }
 }
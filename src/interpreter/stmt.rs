@@ -1,37 +1,60 @@
 use super::{
     expr::*,
-    scanner::{Token, Value},
+    scanner::{Span, Token, Value},
 };
 #[derive(Clone, Debug)]
 pub enum Stmt {
-    Print(Value, usize),
-    Block(Vec<Stmt>, (usize, usize)),
+    Print(Value, Span),
+    Block(Vec<Stmt>, (Span, Span)), // the spans of the opening and closing braces
     Expression(Expr),
     If {
         condition: Expr,
         block: Box<Stmt>,
         elseblock: Option<Box<Stmt>>,
-        lines: (usize, Option<usize>),
+        spans: (Span, Option<Span>), // the spans of the "if" and "else" keywords
     },
     Var {
         name: Token,
         value: Option<Expr>,
+        span: Span,
+        #[allow(dead_code)] // nothing reads docs yet, they are kept for doc extraction
+        doc: Option<String>, // the /// comment above the declaration
     },
     While {
         condition: Expr,
         block: Box<Stmt>,
-        line: usize,
+        span: Span,
+        label: Option<Token>, // 'outer: while ...
     },
     For {
         identifier: Token,
-        iterable: Expr,
+        value: Option<Token>, // for key, value in ...
+        iterable: Box<Expr>,
         block: Box<Stmt>,
-        line: usize,
+        span: Span,
+        label: Option<Token>,
     },
     Fn {
         name: Token,
         params: Vec<Token>,
         body: Box<Stmt>,
+        span: Span,
+        #[allow(dead_code)]
+        doc: Option<String>,
     },
-    Return(Option<Expr>, usize),
+    Struct {
+        name: Token,
+        fields: Vec<Token>,
+        span: Span,
+        #[allow(dead_code)]
+        doc: Option<String>,
+    },
+    Impl {
+        name: Token,
+        methods: Vec<Stmt>, // every one is a Stmt::Fn
+        span: Span,
+    },
+    Return(Option<Expr>, Span),
+    Break(Option<Token>, Span), // the label of the loop to break out of
+    Continue(Option<Token>, Span),
 }
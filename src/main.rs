@@ -1,52 +1,106 @@
-use error::KlangError;
+use diagnostic::{Diagnostic, ErrorFormat};
+use error::{ErrorKind, KlangError};
 use std::path::Path;
 use std::{env, fs};
 mod compiling;
 use compiling::{compiler, vm};
+mod diagnostic;
 mod error;
 mod interpreter;
-use interpreter::{parser, scanner};
+use interpreter::{cst, parser, scanner};
 
 fn main() {
-    let args: Vec<String> = env::args().collect();
+    let mut args: Vec<String> = Vec::new();
+    let mut format = ErrorFormat::Human;
+    let mut print_cst = false;
+    for arg in env::args() {
+        if let Some(name) = arg.strip_prefix("--error-format=") {
+            match ErrorFormat::parse(name) {
+                Some(x) => format = x,
+                None => {
+                    eprintln!("Error: unknown error format \"{name}\" | use human or json");
+                    std::process::exit(1);
+                }
+            }
+        } else if arg == "--cst" {
+            print_cst = true;
+        } else {
+            args.push(arg);
+        }
+    }
     if args.len() < 2 {
-        eprintln!("Error: expected file path | use -c at the end to compile");
+        eprintln!("Error: expected file path | use -c at the end to compile | use --error-format=json for machine readable errors | use --cst to print the file back from its syntax tree");
         std::process::exit(1);
     }
     let filename = &args[1];
     let path = Path::new(&filename);
-    let relfilename = path.file_name().unwrap().to_str().unwrap();
+    //.. and / have no file name, report them with the path they were given as
+    let relfilename = match path.file_name().and_then(|name| name.to_str()) {
+        Some(name) => name,
+        None => fail(
+            format!("File {filename} is not a file!").as_str(),
+            filename,
+            format,
+        ),
+    };
     //run
-    if args.len() >= 2 {
-        if !filename.ends_with(".klang") {
-            KlangError::error(
-                KlangError::RuntimeError,
-                "file must have a \".klang\" extension!",
-                0,
-                relfilename,
-            );
-            panic!("sex");
-        }
-        if fs::metadata(filename).is_err() {
-            KlangError::error(
-                KlangError::RuntimeError,
-                format!("File {filename} is not a file!").as_str(),
-                0,
-                relfilename,
-            );
+    if !filename.ends_with(".klang") {
+        fail(
+            "file must have a \".klang\" extension!",
+            relfilename,
+            format,
+        );
+    }
+    let source = match fs::read_to_string(filename) {
+        Ok(source) => source,
+        Err(_) => fail(
+            format!("File {filename} is not a file!").as_str(),
+            relfilename,
+            format,
+        ),
+    };
+    if print_cst {
+        //the concrete syntax tree gives back the file byte for byte, even a broken one
+        let (tree, diagnostics) = cst::parse(&source);
+        print!("{tree}");
+        if !diagnostics.is_empty() {
+            KlangError::new(diagnostics).report(&source, relfilename, format);
             std::process::exit(1);
-        } else {
-            run_file(filename, relfilename);
         }
+        return;
+    }
+    if let Err(error) = run(&source) {
+        error.report(&source, relfilename, format);
+        std::process::exit(1);
+    }
+}
+
+fn run(source: &str) -> Result<(), KlangError> {
+    let mut scanner = scanner::Scanner::new(source);
+    let (tokens, mut diagnostics) = scanner.scan_tokens();
+    let mut parser = parser::Parser::new(tokens, source);
+    let ast = match parser.parse() {
+        Ok(ast) => ast,
+        Err(error) => {
+            diagnostics.extend(error.diagnostics);
+            Vec::new()
+        }
+    };
+    if !diagnostics.is_empty() {
+        diagnostics.sort_by_key(|diagnostic| diagnostic.span.start);
+        return Err(KlangError::new(diagnostics));
     }
+    let chunk = compiler::Chunk::new(compiler::compile(ast)?);
+    let mut vm = vm::VM::new(chunk);
+    vm.run()
 }
 
-fn run_file(path: &str, relfilename: &str) {
-    let source = fs::read_to_string(path).expect("failed to read file");
-    let mut scanner = scanner::Scanner::new(&source, relfilename);
-    let mut parser = parser::Parser::new(scanner.scan_tokens(), relfilename);
-    let ast = parser.parse();
-    let chunk = compiler::Chunk::new(compiler::compile(ast));
-    let mut vm = vm::VM::new(chunk, relfilename);
-    vm.run();
+fn fail(msg: &str, filename: &str, format: ErrorFormat) -> ! {
+    KlangError::from(Diagnostic::new(
+        ErrorKind::RuntimeError,
+        msg,
+        Default::default(),
+    ))
+    .report("", filename, format);
+    std::process::exit(1);
 }
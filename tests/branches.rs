@@ -0,0 +1,47 @@
+//if, else if and else: only the first branch thats true runs
+mod common;
+
+use common::{check, rejected, run};
+
+#[test]
+fn chains() {
+    check(
+        r#"
+fn sign(n) {
+    if n > 0 { return 1; } else if n < 0 { return -1; }
+    return 0;
+}
+print("{sign(5)} {sign(-2)} {sign(0)}");
+for x in 0..4 {
+    if x == 0 { print("zero"); }
+    else if x == 1 { print("one"); }
+    else if x < 3 { print("small"); }
+    else { print("big"); }
+}
+if false { print("a"); } else if false { print("b"); }
+print("done");
+"#,
+        &["1 -1 0", "zero", "one", "small", "big", "done"],
+    );
+}
+
+#[test]
+fn fizzbuzz() {
+    let output = run(include_str!("../examples/fizzbuzz.klang"));
+    assert!(output.success, "{}", output.stderr);
+    let lines = output.lines();
+    assert_eq!(lines.len(), 99);
+    assert_eq!(
+        lines[..15].join(" "),
+        "1 2 fizz 4 buzz fizz 7 8 fizz buzz 11 fizz 13 14 fizzbuzz"
+    );
+}
+
+#[test]
+fn errors() {
+    rejected("if true { } else if { }", "expected a value before the {");
+    rejected(
+        "if true { } else print(\"x\");",
+        "must start block with a {",
+    );
+}
@@ -0,0 +1,176 @@
+//runs klang programs with closures, a function holds on to the variables it uses from around it
+//(not the whole scopes) so these check it still sees and changes the real ones
+mod common;
+
+use common::check;
+
+#[test]
+fn counter() {
+    //the example from the readme, count lives on after counter returns
+    check(
+        r#"
+fn counter() {
+    let count = 0;
+    return fn() {
+        count += 1;
+        return count;
+    };
+}
+let next = counter();
+print("{next()} {next()}");
+let other = counter();
+print("{other()} {next()}");
+"#,
+        &["1 2", "1 3"],
+    );
+}
+
+#[test]
+fn by_reference() {
+    //the closure sees changes made after it was made, and the outside sees its changes,
+    //while the scope is still running and after it ended
+    check(
+        r#"
+fn pair() {
+    let x = 1;
+    let get = fn() { return x; };
+    let set = fn(v) { x = v; };
+    x = 10;
+    print("{get()}");
+    set(7);
+    print("{x} {get()}");
+    return [get, set];
+}
+let p = pair();
+p[1](42);
+print("{p[0]()}");
+let total = 0;
+let add = fn(n) { total += n; };
+add(2);
+add(3);
+total *= 10;
+add(1);
+print("{total}");
+"#,
+        &["10", "7 7", "42", "51"],
+    );
+}
+
+#[test]
+fn per_iteration() {
+    //every round of a loop has its own scope, so each closure keeps its own variables
+    check(
+        r#"
+let fs = [0, 0, 0];
+for k in 0..3 {
+    let doubled = k * 2;
+    fs[k] = fn() { return k * 10 + doubled; };
+}
+print("{fs[0]()} {fs[1]()} {fs[2]()}");
+let gs = [0, 0];
+let j = 0;
+while j < 2 {
+    let seen = j;
+    gs[j] = fn() { seen += 100; return seen; };
+    j += 1;
+}
+print("{gs[0]()} {gs[1]()} {gs[0]()}");
+"#,
+        &["0 12 24", "100 101 200"],
+    );
+}
+
+#[test]
+fn inner_functions() {
+    //a function calls itself and the ones below it, also after the scope it was made in ended
+    check(
+        r#"
+fn make() {
+    fn fact(n) {
+        if n < 2 { return 1; }
+        return n * fact(n - 1);
+    }
+    fn a(n) { return b(n) + 1; }
+    fn b(n) { return n * 2; }
+    print("{a(3)}");
+    return fact;
+}
+let f = make();
+print("{f(5)}");
+fn outer() {
+    let big = 0..200;
+    fn inner() { return 1; }
+    return inner();
+}
+let sum = 0;
+for i in 0..2000 {
+    sum += outer();
+}
+print("{sum}");
+"#,
+        &["7", "120", "2000"],
+    );
+}
+
+#[test]
+fn declared_later() {
+    //variables made after the closure in the scope around it are still captured,
+    //and they keep working after that scope ended
+    check(
+        r#"
+fn outer() {
+    let g = fn() { return b; };
+    let b = 2;
+    return g;
+}
+print("{outer()()}");
+fn make() {
+    fn even(n) {
+        if n == 0 { return true; }
+        return odd(n - 1);
+    }
+    fn odd(n) {
+        if n == 0 { return false; }
+        return even(n - 1);
+    }
+    return even;
+}
+let even = make();
+print("{even(4)} {even(7)}");
+fn counter() {
+    let next = fn() {
+        count += 1;
+        return count;
+    };
+    let count = 10;
+    return next;
+}
+let next = counter();
+print("{next()} {next()}");
+fn setup() {
+    return fn() { return later(); };
+}
+let f = setup();
+fn later() { return "late"; }
+print("{f()}");
+"#,
+        &["2", "true false", "11 12", "late"],
+    );
+    //the closures own lets stay its own, every call gets new ones
+    check(
+        r#"
+fn make() {
+    fn fact(n) {
+        let r = 1;
+        if n > 1 {
+            r = n * fact(n - 1);
+        }
+        return r;
+    }
+    return fact;
+}
+print("{make()(5)}");
+"#,
+        &["120"],
+    );
+}
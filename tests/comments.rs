@@ -0,0 +1,51 @@
+//line, block and doc comments are skipped wherever they are
+mod common;
+
+use common::{check, rejected};
+
+#[test]
+fn block() {
+    check(
+        r#"
+/* a /* nested */ still comment */
+let x = 1; /* inline */ let y = 2;
+/*
+let x = 5;
+/* commented out code with its own */
+*/
+let w = 4 /* in */ + /* an expression */ 1;
+print("{x} {y} {w}"); // trailing
+/**/ print("after");
+"#,
+        &["1 2 5", "after"],
+    );
+    rejected(
+        "let x = 1; /* never /* closed */",
+        "unterminated block comment",
+    );
+}
+
+#[test]
+fn doc() {
+    //a /// above a fn, let or struct documents it, anywhere else its just a comment
+    check(
+        r#"
+/// adds one
+fn add(n) { return n + 1; }
+/// the answer
+let z = 42;
+/// a point
+struct P { x }
+let x = /// not a doc
+  1;
+/// on a print
+print("{add(x)} {z}");
+if true {
+    /// inside a block
+    let y = 2;
+}
+/// at the end
+"#,
+        &["2 42"],
+    );
+}
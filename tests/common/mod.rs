@@ -0,0 +1,64 @@
+//runs klang programs for the tests, every test file only uses some of these
+#![allow(dead_code)]
+use std::process::Command;
+use std::sync::atomic::{AtomicUsize, Ordering};
+
+pub struct Output {
+    pub success: bool,
+    //none when it was killed, 101 when it panicked
+    pub code: Option<i32>,
+    pub stdout: String,
+    pub stderr: String,
+}
+
+impl Output {
+    //what it printed, one line per print
+    pub fn lines(&self) -> Vec<&str> {
+        self.stdout.lines().collect()
+    }
+}
+
+//writes the source to a file and runs klang on it with the extra arguments
+pub fn run_with(source: &str, args: &[&str]) -> Output {
+    static COUNTER: AtomicUsize = AtomicUsize::new(0);
+    let path = std::env::temp_dir().join(format!(
+        "klang_test_{}_{}.klang",
+        std::process::id(),
+        COUNTER.fetch_add(1, Ordering::SeqCst)
+    ));
+    std::fs::write(&path, source).unwrap();
+    let output = Command::new(env!("CARGO_BIN_EXE_klang"))
+        .arg(&path)
+        .args(args)
+        .output()
+        .unwrap();
+    std::fs::remove_file(&path).unwrap();
+    Output {
+        success: output.status.success(),
+        code: output.status.code(),
+        stdout: String::from_utf8(output.stdout).unwrap(),
+        stderr: String::from_utf8(output.stderr).unwrap(),
+    }
+}
+
+pub fn run(source: &str) -> Output {
+    run_with(source, &[])
+}
+
+//the program runs and prints exactly these lines
+pub fn check(source: &str, expected: &[&str]) {
+    let output = run(source);
+    assert!(output.success, "should run:\n{source}\n{}", output.stderr);
+    assert_eq!(output.lines(), expected, "{source}");
+}
+
+//the program fails with an error that says this
+pub fn rejected(source: &str, message: &str) {
+    let output = run(source);
+    assert!(!output.success, "should not run:\n{source}");
+    assert!(
+        output.stderr.contains(message),
+        "expected {message:?} in:\n{}",
+        output.stderr
+    );
+}
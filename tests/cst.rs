@@ -0,0 +1,70 @@
+//prints files back through the concrete syntax tree (klang file.klang --cst) and checks
+//that every byte comes back, comments, whitespace and broken code included
+mod common;
+
+//runs klang --cst on the source, returns what it printed
+fn round_trip(source: &str) -> String {
+    common::run_with(source, &["--cst"]).stdout
+}
+
+fn check(source: &str) {
+    assert_eq!(
+        round_trip(source),
+        source,
+        "{source:?} should come back the same"
+    );
+}
+
+#[test]
+fn examples() {
+    let mut count = 0;
+    for entry in std::fs::read_dir("examples").unwrap() {
+        let path = entry.unwrap().path();
+        if path.extension().is_some_and(|x| x == "klang") {
+            check(&std::fs::read_to_string(&path).unwrap());
+            count += 1;
+        }
+    }
+    assert!(count > 0, "there should be examples to go through");
+}
+
+#[test]
+fn trivia() {
+    check("");
+    check("   \n\t\n");
+    check("// a comment\nlet x = 1; // after\n");
+    check("/* block /* nested */ still block */ let x = 1;");
+    check("/// docs\n/// more docs\nfn f(a, b) {\n    return a + b;\n}\n");
+    check("let v = [\n    1, /// not a doc\n    2,\n];\n");
+    check("let x = 1;\r\nlet y = 2;\r\n");
+    check("let café = \"ünïcødé 😀\";\n");
+    check("let x = 1;"); //no newline at the end
+}
+
+#[test]
+fn statements() {
+    check("if a { b(); } else if c { d(); } else { e(); }\n");
+    check("'outer: while true {\n    for i, x in v {\n        break 'outer;\n    }\n}\n");
+    check("let f = fn(x) { return x * 2; };\nf(1)(2);\n");
+    check("let m = {\"a\": 1, 2: [3, 4]};\nm[\"a\"] += v[1..-1..2];\n");
+    check(
+        "struct P { x, y }\nimpl P {\n    fn len(self) { return self.x; }\n}\nP::new(1).len();\n",
+    );
+    check("print(\"hi {1 + 2} \\{x\\}\");\nlet r = r#\"raw \" {x}\"#;\n");
+    check("let s = \"\"\"\n    multi\n      line\n    \"\"\";\n");
+}
+
+#[test]
+fn broken() {
+    check("let x = \"abc\nlet y = 1;\n");
+    check("let x = r#\"abc");
+    check("let x = \"\"\"\nnever closed");
+    check("/* never closed\nlet x = 1;\n");
+    check("/* /* */ still open");
+    check("let x = \"bad \\q escape \\u{zz}\";\n");
+    check("let x = 1 @ 2;\nlet y = #;\n");
+    check("let x = 0x;\nlet y = 1.;\nlet z = 'a;\n");
+    check("if x { let y = (1 + ;\n");
+    check("}}) ]] let x = [1, 2;\n");
+    check("fn f( { while { for in {");
+}
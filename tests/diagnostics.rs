@@ -0,0 +1,71 @@
+//checks how errors are shown: where they point, the source line and the carets under it
+mod common;
+
+use common::run;
+
+//the errors of a program that shouldnt run
+fn errors(source: &str) -> String {
+    let output = run(source);
+    assert!(!output.success, "should not run:\n{source}");
+    output.stderr
+}
+
+//the error after its message and the --> line (the file is a temp file with a random name)
+fn snippet(errors: &str) -> String {
+    errors.lines().skip(2).collect::<Vec<_>>().join("\n")
+}
+
+//the line:column of the --> line
+fn position(errors: &str) -> &str {
+    let line = errors.lines().find(|line| line.contains("-->")).unwrap();
+    line.split(".klang:").nth(1).unwrap()
+}
+
+#[test]
+fn one_line() {
+    let shown = errors("let a = 1;\nlet b = a +\n  (2 * c;\n");
+    assert!(shown.starts_with("[ParserError]: expected \")\" after expression"));
+    assert_eq!(position(&shown), "3:9");
+    assert_eq!(
+        snippet(&shown),
+        "  |\n3 |   (2 * c;\n  |         ^\n  = note: expected RightParen but found Semicolon"
+    );
+    let shown = errors("let x = [1, 2];\nprint(\"{x[5]}\");\n");
+    assert!(shown.starts_with("[RuntimeError]: index 5 is out of bounds, the length is 2"));
+    assert_eq!(position(&shown), "2:9");
+    assert_eq!(
+        snippet(&shown),
+        "  |\n2 | print(\"{x[5]}\");\n  |         ^^^^"
+    );
+    //a tab before the error stays a tab under it so the carets line up
+    let shown = errors("\tlet x = @;\n");
+    assert_eq!(snippet(&shown), "  |\n1 | \tlet x = @;\n  | \t        ^");
+}
+
+#[test]
+fn multi_line() {
+    assert_eq!(
+        snippet(&errors("let z = 1 +\n    \"a\";\n")),
+        "  |\n1 | let z = 1 +\n  |         ^^^\n2 |     \"a\";\n  |     ^^^"
+    );
+    //long spans show the first two lines and the last one
+    assert_eq!(
+        snippet(&errors("let v = [\n1,\n2,\n3,\n4,\n5] + 1;\n")),
+        "  |\n1 | let v = [\n  |         ^\n2 | 1,\n  | ^^\n...\n6 | 5] + 1;\n  | ^^^^^^"
+    );
+}
+
+#[test]
+fn end_of_file() {
+    //these are only found at the end of the file, they still point at its last line
+    let shown = errors("fn f() {\n    let x = 1;\n\n");
+    assert!(shown.starts_with("[ParserError]: must end block with a }"));
+    assert_eq!(position(&shown), "2:15");
+    assert!(
+        snippet(&shown).starts_with("  |\n2 |     let x = 1;\n  |               ^"),
+        "{shown}"
+    );
+    let shown = errors("let x = 1 +\n");
+    assert_eq!(position(&shown), "1:12");
+    assert_eq!(snippet(&shown), "  |\n1 | let x = 1 +\n  |            ^");
+}
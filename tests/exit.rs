@@ -0,0 +1,45 @@
+//every kind of error ends klang with exit code 1 and a message, never with a panic
+mod common;
+
+use common::{rejected, run, run_with};
+
+//fails with code 1 and no panic, returns the errors
+fn fails(source: &str) -> String {
+    let output = run(source);
+    assert_eq!(output.code, Some(1), "{source}\n{}", output.stderr);
+    assert!(!output.stderr.contains("panicked"), "{}", output.stderr);
+    output.stderr
+}
+
+#[test]
+fn codes() {
+    assert_eq!(run("print(\"ok\");").code, Some(0));
+    assert!(fails("let a = @;").starts_with("[ScannerError]"));
+    assert!(fails("let a = 1 +;").starts_with("[ParserError]"));
+    assert!(fails("let x = 1 / 0;").starts_with("[RuntimeError]: division by zero"));
+    assert!(fails("fn f(n) { return f(n + 1); }\nf(0);").contains("stack overflow"));
+    //the error stops the program where it happened
+    let output = run("print(\"before\");\nlet x = nope;\nprint(\"after\");");
+    assert_eq!(output.code, Some(1));
+    assert_eq!(output.lines(), ["before"]);
+    let output = run_with("print(\"ok\");", &["--error-format=xml"]);
+    assert_eq!(output.code, Some(1));
+}
+
+#[test]
+fn type_names() {
+    //these used to panic in the parser
+    for name in ["int", "float", "bool", "string"] {
+        let shown = fails(&format!("let x = {name};"));
+        assert!(
+            shown.starts_with(&format!(
+                "[ParserError]: expected a value, found type `{name}`"
+            )),
+            "{shown}"
+        );
+    }
+    rejected(
+        "let x = 1;\nlet y = x + int;",
+        "int, float, string and bool are names of types, not variables",
+    );
+}
@@ -0,0 +1,28 @@
+//names of variables and functions: letters from any language, digits after the first one and _
+mod common;
+
+use common::{check, rejected};
+
+#[test]
+fn names() {
+    check(
+        r#"
+let my_var = 1;
+let _tmp = 2;
+let größe = 3;
+let 名前 = 4;
+let café2 = my_var + _tmp;
+let _ = 9;
+let x_ = _;
+fn über_fn(x) { return x * größe; }
+print("{my_var} {_tmp} {größe} {名前} {café2} {über_fn(2)} {x_}");
+"#,
+        &["1 2 3 4 3 6 9"],
+    );
+}
+
+#[test]
+fn bad_names() {
+    rejected("let 2x = 1;", "must define a variable name");
+    rejected("let a€ = 1;", "unexpected character");
+}
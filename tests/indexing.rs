@@ -0,0 +1,92 @@
+//indexing and slicing vectors and strings, negative indices count from the end everywhere
+mod common;
+
+use common::{check, rejected};
+
+#[test]
+fn index() {
+    check(
+        r#"
+let v = [1, 2, 3, 4, 5];
+print("{v[0]} {v[-1]} {v[-5]}");
+v[-1] = 50;
+print("{v}");
+let s = "hello";
+print("{s[0]}{s[-1]}");
+"#,
+        &["1 5 1", "[1, 2, 3, 4, 50]", "ho"],
+    );
+    rejected(
+        "let v = [1];\nprint(\"{v[-2]}\");",
+        "index -2 is out of bounds",
+    );
+    rejected(
+        "let v = [1];\nprint(\"{v[1]}\");",
+        "index 1 is out of bounds",
+    );
+    rejected(
+        "let v = [1];\nprint(\"{v[0.5]}\");",
+        "index must be a whole number but its 0.5",
+    );
+}
+
+#[test]
+fn slices() {
+    check(
+        r#"
+let v = [1, 2, 3, 4, 5];
+print("{v[1..3]} {v[-2..]} {v[..-3]} {v[..]}");
+print("{v[2..1]} {v[5..]}");
+let s = "hello";
+print("{s[1..-1]}");
+"#,
+        &["[2, 3] [4, 5] [1, 2] [1, 2, 3, 4, 5]", "[] []", "ell"],
+    );
+}
+
+#[test]
+fn natives() {
+    check(
+        r#"
+let v = [1, 2, 3];
+print("{std::get(v, -2)} {std::set(v, 9, -3)} {std::remove(v, -1)}");
+print("{std::insert(v, 0, -1)} {std::insert(v, 0, -3)}");
+print("{std::insert(v, 0, std::len(v))} {v}");
+"#,
+        &[
+            "2 [9, 2, 3] [1, 2]",
+            "[1, 2, 0, 3] [0, 1, 2, 3]",
+            "[1, 2, 3, 0] [1, 2, 3]",
+        ],
+    );
+    rejected(
+        "let v = [1];\nprint(\"{std::insert(v, 0, -2)}\");",
+        "index -2 is out of bounds",
+    );
+    rejected(
+        "let v = [1];\nprint(\"{std::insert(v, 0, 2)}\");",
+        "index 2 is out of bounds",
+    );
+}
+
+#[test]
+fn steps() {
+    check(
+        r#"
+let v = [0, 1, 2, 3, 4, 5, 6];
+print("{v[0..6..2]} {v[1....3]} {v[..-1..3]} {v[....2]}");
+let s = "héllo!";
+print("{s[0..6..2]} {s[1..]} {s[..-1..2]}");
+"#,
+        &["[0, 2, 4] [1, 4] [0, 3] [0, 2, 4, 6]", "hlo éllo! hlo"],
+    );
+    rejected(
+        "let v = [1, 2];\nprint(\"{v[0..2..0]}\");",
+        "slice step must be at least 1 but its 0",
+    );
+    rejected(
+        "let v = [1, 2];\nprint(\"{v[0..3]}\");",
+        "index 3 is out of bounds, the length is 2",
+    );
+    rejected("let v = 5;\nprint(\"{v[0..1]}\");", "cant slice 5");
+}
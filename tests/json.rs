@@ -0,0 +1,63 @@
+//--error-format=json prints every error as one json object per line
+mod common;
+
+use common::run_with;
+
+//the json lines of a program that shouldnt run, with the temp file name taken out
+fn json(source: &str) -> Vec<String> {
+    let output = run_with(source, &["--error-format=json"]);
+    assert!(!output.success, "should not run:\n{source}");
+    assert!(output.stdout.is_empty());
+    output
+        .stderr
+        .lines()
+        .map(|line| {
+            let start = line.find("\"file\":\"").unwrap() + 8;
+            let end = start + line[start..].find('"').unwrap();
+            format!("{}FILE{}", &line[..start], &line[end..])
+        })
+        .collect()
+}
+
+#[test]
+fn shape() {
+    assert_eq!(
+        json("let a = 1 +;\nlet b = @;\n"),
+        [
+            r#"{"kind":"ParserError","message":"expected value found Semicolon","file":"FILE","line":1,"column":12,"span":{"start":11,"end":12,"line":1,"column":12,"end_line":1,"end_column":13},"notes":[],"help":null}"#,
+            r#"{"kind":"ScannerError","message":"unexpected character","file":"FILE","line":2,"column":9,"span":{"start":21,"end":22,"line":2,"column":9,"end_line":2,"end_column":10},"notes":[],"help":null}"#,
+        ]
+    );
+    assert_eq!(
+        json("let v = [1];\nprint(\"{v[3]}\");\n"),
+        [
+            r#"{"kind":"RuntimeError","message":"index 3 is out of bounds, the length is 1","file":"FILE","line":2,"column":9,"span":{"start":21,"end":25,"line":2,"column":9,"end_line":2,"end_column":13},"notes":[],"help":null}"#
+        ]
+    );
+}
+
+#[test]
+fn notes_and_escapes() {
+    assert_eq!(
+        json("while { }\n"),
+        [
+            r#"{"kind":"ParserError","message":"expected a value before the {","file":"FILE","line":1,"column":7,"span":{"start":6,"end":7,"line":1,"column":7,"end_line":1,"end_column":8},"notes":["a { right after if, while or for ... in starts the block"],"help":"put the condition between the keyword and the {, or the map in ( )"}"#
+        ]
+    );
+    let lines = json("let x = nope;\n");
+    assert!(
+        lines[0].contains(r#""message":"variable \"nope\" do not exist""#),
+        "{}",
+        lines[0]
+    );
+}
+
+#[test]
+fn formats() {
+    let output = run_with("print(\"ok\");", &["--error-format=json"]);
+    assert!(output.success);
+    assert_eq!(output.lines(), ["ok"]);
+    let output = run_with("print(\"ok\");", &["--error-format=xml"]);
+    assert!(!output.success);
+    assert!(output.stderr.contains("unknown error format \"xml\""));
+}
@@ -0,0 +1,256 @@
+//runs klang programs with loops. break and continue are patched into jumps by hand by the
+//compiler so most of these check they land in the right place and close the right scopes
+mod common;
+
+use common::{check, rejected, run};
+use std::time::{Duration, Instant};
+
+#[test]
+fn while_loop() {
+    check(
+        r#"
+let i = 0;
+while i < 10 {
+    i += 1;
+    if i % 2 == 0 {
+        continue;
+    }
+    if i > 7 {
+        break;
+    }
+    print("{i}");
+}
+print("after {i}");
+"#,
+        &["1", "3", "5", "7", "after 9"],
+    );
+}
+
+#[test]
+fn for_loop() {
+    check(
+        r#"
+for i in 0..10 {
+    if i == 2 { continue; }
+    if i == 6 { break; }
+    print("{i}");
+}
+for ch in "abcdef" {
+    if ch == "b" { continue; }
+    if ch == "e" { break; }
+    print("{ch}");
+}
+for k, v in {"a": 1, "b": 2, "c": 3} {
+    if k == "b" { continue; }
+    print("{k}={v}");
+}
+"#,
+        &["0", "1", "3", "4", "5", "a", "c", "d", "a=1", "c=3"],
+    );
+}
+
+#[test]
+fn labels() {
+    //continue 'outer skips the rest of the while, break 'outer leaves it from inside the for
+    check(
+        r#"
+let n = 0;
+'outer: while n < 5 {
+    n += 1;
+    for i in 0..5 {
+        if i == n {
+            continue 'outer;
+        }
+        if n == 4 {
+            break 'outer;
+        }
+        print("{n}{i}");
+    }
+    print("never");
+}
+print("n {n}");
+"#,
+        &["10", "20", "21", "30", "31", "32", "n 4"],
+    );
+    check(
+        r#"
+let hits = 0;
+'a: for x in 0..3 {
+    'b: for y in 0..3 {
+        while true {
+            if y == 1 { continue 'b; }
+            if x == 2 { break 'a; }
+            hits += 1;
+            break;
+        }
+    }
+}
+print("{hits}");
+"#,
+        &["4"],
+    );
+}
+
+#[test]
+fn nested_blocks() {
+    //the jumps have to close the blocks and ifs they are in, x is in the outer scope
+    check(
+        r#"
+let x = 100;
+for i in 0..4 {
+    let a = i;
+    {
+        let b = a * 10;
+        if b > 5 {
+            {
+                if b == 20 {
+                    continue;
+                }
+                if b == 30 {
+                    break;
+                }
+            }
+        }
+        print("{b}");
+    }
+}
+let j = 0;
+while true {
+    j += 1;
+    {
+        let k = j;
+        if k < 3 { { continue; } }
+        else { if k == 3 { { { break; } } } }
+    }
+}
+print("{j} {x}");
+"#,
+        &["0", "10", "3 100"],
+    );
+}
+
+#[test]
+fn empty_inner_loops() {
+    //an empty loop is skipped without opening a scope, the outer loop must not notice
+    check(
+        r#"
+for i in 0..3 {
+    for j in 0..i {
+        print("{i}{j}");
+    }
+    for k in 5..5 {
+        print("never");
+    }
+    for ch in "" {
+        print("never");
+    }
+    print("end {i}");
+}
+let w = 0;
+while w < 2 {
+    w += 1;
+    for z in 3..1 { print("never"); }
+    print("w {w}");
+}
+"#,
+        &["end 0", "10", "end 1", "20", "21", "end 2", "w 1", "w 2"],
+    );
+}
+
+#[test]
+fn many_rounds() {
+    //breaking out of a for drops what it had left to go through, nothing piles up
+    check(
+        r#"
+let s = 0;
+for r in 0..1000 {
+    for i in 0..10 {
+        if i == 3 { break; }
+        s += i;
+    }
+}
+fn first_even(v) {
+    for x in v {
+        if x % 2 == 0 {
+            return x;
+        }
+    }
+    return -1;
+}
+let total = 0;
+for i in 0..3 {
+    while true {
+        total += first_even([1, 3, i * 2 + 2]);
+        break;
+    }
+}
+print("{s} {total} {first_even([1, 3])}");
+"#,
+        &["3000 12 -1"],
+    );
+}
+
+#[test]
+fn not_across_functions() {
+    rejected(
+        r#"
+while true {
+    let f = fn() {
+        break;
+    };
+    break;
+}
+"#,
+        "cant break outside of a loop",
+    );
+    rejected(
+        r#"
+'outer: for i in 0..3 {
+    fn g() {
+        continue 'outer;
+    }
+}
+"#,
+        "cant continue outside of a loop",
+    );
+    rejected(
+        r#"
+'outer: for i in 0..3 {
+    break 'inner;
+}
+"#,
+        "there is no loop called 'inner",
+    );
+    rejected("break;", "cant break outside of a loop");
+}
+
+#[test]
+fn iterables() {
+    //maps go over their keys in order, strings over their characters
+    check(
+        r#"
+let m = {"b": 1, "a": 2, 3: 4};
+for k in m { print("{k}"); }
+for ch in "héllo" { print("{ch}"); }
+fn nums() { return [3, 1, 2]; }
+for n in nums() { print("{n}"); }
+for i in 10..0 { print("never"); }
+for i in 0..10..4 { print("{i}"); }
+for v in [[1, 2], [3]] { print("{v}"); }
+"#,
+        &[
+            "3", "a", "b", "h", "é", "l", "l", "o", "3", "1", "2", "0", "4", "8", "[1, 2]", "[3]",
+        ],
+    );
+    rejected("for x in 5 { }", "cant loop over 5");
+}
+
+#[test]
+fn long_loop() {
+    //every round takes its value off the end, so going over a lot of values stays quick
+    let start = Instant::now();
+    let output = run("let s = 0;\nfor i in 0..100000 { s += i; }\nprint(\"{s}\");");
+    assert!(output.success, "{}", output.stderr);
+    assert_eq!(output.lines(), ["4999950000"]);
+    assert!(start.elapsed() < Duration::from_secs(30));
+}
@@ -0,0 +1,106 @@
+//maps: literals, reading and changing keys, the std functions for them and going over them
+mod common;
+
+use common::{check, rejected};
+
+#[test]
+fn literals() {
+    //keys are kept in order, bools before numbers before strings
+    check(
+        r#"
+let m = {"b": 1, "a": 2, 3: "three", true: [1]};
+print("{m}");
+let e = {};
+print("{e} {std::len(e)} {std::len(m)}");
+let nested = {"x": {"y": [1, 2]}, "z": {}};
+print("{nested}");
+"#,
+        &[
+            "{true: [1], 3: three, a: 2, b: 1}",
+            "{} 0 4",
+            "{x: {y: [1, 2]}, z: {}}",
+        ],
+    );
+    rejected("let m = {[1]: 2};", "cant use [1] as a map key");
+    rejected(
+        r#"let m = {"a" 1};"#,
+        "missing : between the key and the value",
+    );
+}
+
+#[test]
+fn keys() {
+    check(
+        r#"
+let m = {"a": 1, 3: "three", true: [1]};
+let a = "a";
+let c = "c";
+print("{m[a]} {m[3]} {m[true]}");
+m[c] = 5;
+m[a] += 10;
+print("{m}");
+let nested = {"x": {"y": [1, 2]}};
+let x = "x";
+let y = "y";
+nested[x][y][0] = 9;
+print("{nested}");
+"#,
+        &[
+            "1 three [1]",
+            "{true: [1], 3: three, a: 11, c: 5}",
+            "{x: {y: [9, 2]}}",
+        ],
+    );
+    rejected(
+        "let m = {\"a\": 1};\nlet b = \"b\";\nprint(\"{m[b]}\");",
+        "the map has no key b",
+    );
+}
+
+#[test]
+fn natives() {
+    check(
+        r#"
+let m = {"b": 1, "a": 2};
+let a = "a";
+let z = "z";
+print("{std::keys(m)} {std::values(m)}");
+print("{std::has(m, a)} {std::has(m, z)}");
+let n = std::remove(m, a);
+print("{n} {m}");
+"#,
+        &["[a, b] [2, 1]", "true false", "{b: 1} {a: 2, b: 1}"],
+    );
+}
+
+#[test]
+fn loops() {
+    check(
+        r#"
+let m = {"b": 1, "a": 2};
+for k in m { print("{k}"); }
+for k, v in m { print("{k}={v}"); }
+for k, v in {"x": 1} { print("{k}={v}"); }
+"#,
+        &["a", "b", "a=2", "b=1", "x=1"],
+    );
+}
+
+#[test]
+fn conditions() {
+    //right after if, while or for ... in a { is the block, unless a key and a : follow
+    rejected("while { print(\"x\"); }", "expected a value before the {");
+    rejected("let x = 1;\nif { }", "expected a value before the {");
+    rejected("for k in { } { }", "expected a value before the {");
+    //in brackets they are values again
+    check(
+        r#"
+struct E {}
+fn size(m) { return std::len(m); }
+if size({}) == 0 { print("call"); }
+if std::len([{}, E {}]) == 2 { print("vec"); }
+if (E {}) != 1 { print("group"); }
+"#,
+        &["call", "vec", "group"],
+    );
+}
@@ -0,0 +1,45 @@
+//number literals: decimal, hex, binary and octal, floats, exponents and _ between digits
+mod common;
+
+use common::{check, rejected};
+
+#[test]
+fn forms() {
+    check(
+        r#"
+print("{255} {2.5} {0xFF} {0xff} {0b1010} {0o17}");
+print("{1_000_000} {0xFF_FF} {0b1_0} {1_0.2_5}");
+print("{1.5e-3} {1E+2} {2e3} {1e1_0}");
+print("{1..3} {0x2..0b100}");
+"#,
+        &[
+            "255 2.5 255 255 10 15",
+            "1000000 65535 2 10.25",
+            "0.0015 100 2000 10000000000",
+            "[1, 2] [2, 3]",
+        ],
+    );
+}
+
+#[test]
+fn underscores() {
+    for bad in [
+        "1__0", "1_", "1._5", "1.5_", "1e_3", "0xF_", "0xF__F", "0x__F",
+    ] {
+        rejected(
+            &format!("print(\"{{{bad}}}\");"),
+            "_ can only go between digits",
+        );
+    }
+}
+
+#[test]
+fn bad() {
+    rejected("let x = 1.;", "float cant end with a dot");
+    rejected("let x = 1e;", "missing the exponent after the e");
+    rejected("let x = 0x;", "missing digits after 0x");
+    rejected("let x = 0b102;", "2 is not a valid binary digit");
+    rejected("let x = 0o8;", "8 is not a valid octal digit");
+    rejected("let x = 0xFFFFFFFFFFFFFFFFFF;", "hex number is too big");
+    rejected("let x = 99999999999999999999;", "failed to parse integer");
+}
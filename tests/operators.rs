@@ -0,0 +1,186 @@
+//runs klang on small programs and checks how every pair of binary operators groups
+mod common;
+
+//the symbol of each binary operator, by its name in the parser
+const SYMBOLS: [(&str, &str); 13] = [
+    ("Or", "||"),
+    ("And", "&&"),
+    ("EqualEqual", "=="),
+    ("BangEqual", "!="),
+    ("Less", "<"),
+    ("LessEqual", "<="),
+    ("Greater", ">"),
+    ("GreaterEqual", ">="),
+    ("Plus", "+"),
+    ("Minus", "-"),
+    ("Star", "*"),
+    ("Slash", "/"),
+    ("Modulo", "%"),
+];
+
+//operator and how tight it binds, read out of precedence() in the parser so they cant disagree
+fn operators() -> Vec<(&'static str, u8)> {
+    let parser = std::fs::read_to_string("src/interpreter/parser.rs").unwrap();
+    let start = parser.find("fn precedence(").unwrap();
+    let end = start + parser[start..].find("_ => None").unwrap();
+    let mut table = Vec::new();
+    let mut waiting = Vec::new(); //operators of the arm we are in, until its Some(x)
+    let mut some = false;
+    for word in parser[start..end].split(|ch: char| !ch.is_alphanumeric()) {
+        if let Some((_, symbol)) = SYMBOLS.iter().find(|(name, _)| *name == word) {
+            waiting.push(*symbol);
+        } else if some {
+            let precedence: u8 = word.parse().unwrap();
+            table.extend(waiting.drain(..).map(|symbol| (symbol, precedence)));
+        }
+        some = word == "Some";
+    }
+    assert_eq!(
+        table.len(),
+        SYMBOLS.len(),
+        "every operator should be in the table"
+    );
+    table
+}
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+enum V {
+    N(f64),
+    B(bool),
+}
+
+impl V {
+    fn source(self) -> String {
+        match self {
+            V::N(x) => x.to_string(),
+            V::B(x) => x.to_string(),
+        }
+    }
+}
+
+//what the vm does with each operator, None is a runtime error
+fn apply(op: &str, left: V, right: V) -> Option<V> {
+    match (op, left, right) {
+        ("||", V::B(x), V::B(y)) => Some(V::B(x || y)),
+        ("&&", V::B(x), V::B(y)) => Some(V::B(x && y)),
+        ("==", x, y) => Some(V::B(x == y)),
+        ("!=", x, y) => Some(V::B(x != y)),
+        ("<", V::N(x), V::N(y)) => Some(V::B(x < y)),
+        ("<=", V::N(x), V::N(y)) => Some(V::B(x <= y)),
+        (">", V::N(x), V::N(y)) => Some(V::B(x > y)),
+        (">=", V::N(x), V::N(y)) => Some(V::B(x >= y)),
+        ("+", V::N(x), V::N(y)) => Some(V::N(x + y)),
+        ("-", V::N(x), V::N(y)) => Some(V::N(x - y)),
+        ("*", V::N(x), V::N(y)) => Some(V::N(x * y)),
+        ("/", V::N(x), V::N(y)) if y != 0.0 => Some(V::N(x / y)),
+        ("%", V::N(x), V::N(y)) if y != 0.0 => Some(V::N(x % y)),
+        _ => None,
+    }
+}
+
+fn logical(op: &str) -> bool {
+    op == "||" || op == "&&"
+}
+
+//runs a program, returns whether it succeeded and what it printed
+fn run(source: &str) -> (bool, String) {
+    let output = common::run(source);
+    (output.success, output.stdout.trim().to_string())
+}
+
+//prints every expression and compares it to what it should be
+fn check(cases: &[(&str, &str)]) {
+    for (expression, expected) in cases {
+        let (success, output) = run(&format!("print(\"{{{expression}}}\");"));
+        assert!(success, "{expression} should run");
+        assert_eq!(&output, expected, "{expression}");
+    }
+}
+
+#[test]
+fn every_operator_pair() {
+    let operators = operators();
+    for &(op1, p1) in &operators {
+        for &(op2, p2) in &operators {
+            //a op1 b op2 c groups to the right only when op2 binds tighter
+            let right = p2 > p1;
+            //a leaf is a bool when a logical operator uses it and a number otherwise
+            let leaf = |op: &str, n: f64, b: bool| if logical(op) { V::B(b) } else { V::N(n) };
+            let a = leaf(op1, 7.0, true);
+            let b = leaf(if right { op2 } else { op1 }, 3.0, false);
+            let c = leaf(op2, 2.0, false);
+            let expected = if right {
+                apply(op2, b, c).and_then(|bc| apply(op1, a, bc))
+            } else {
+                apply(op1, a, b).and_then(|ab| apply(op2, ab, c))
+            };
+            let expression = format!("{} {op1} {} {op2} {}", a.source(), b.source(), c.source());
+            let (success, output) = run(&format!("print(\"{{{expression}}}\");"));
+            match expected {
+                Some(value) => {
+                    assert!(success, "{expression} should run");
+                    assert_eq!(output, value.source(), "{expression}");
+                }
+                None => assert!(!success, "{expression} should be a runtime error"),
+            }
+        }
+    }
+}
+
+#[test]
+fn left_associative() {
+    check(&[
+        ("10 - 3 - 2", "5"),
+        ("100 / 10 / 5", "2"),
+        ("2 * 3 % 4", "2"),
+        ("7 % 4 % 2", "1"),
+        ("10 - 2 * 3", "4"),
+        ("1 - 2 + 3", "2"),
+        ("1 == 1 == true", "true"),
+        ("1 < 2 == 3 < 4", "true"),
+        ("true || false && false", "true"),
+        ("(10 - 3) - 2 == 10 - 3 - 2", "true"),
+    ]);
+}
+
+#[test]
+fn unary() {
+    check(&[
+        ("-2 * 3", "-6"),
+        ("--5", "5"),
+        ("- -2", "2"),
+        ("2 - -3", "5"),
+        ("2--3", "5"),
+        ("10---1", "9"),
+        ("2 - --3", "-1"),
+        ("!!true", "true"),
+        ("!true == false", "true"),
+        ("-std::sqrt(4)", "-2"),
+        ("-std::abs(-3) + 1", "-2"),
+        ("-2 + 5", "3"),
+        ("!!!true", "false"),
+        ("-[1, 2][0]", "-1"),
+        ("-(1..3)[1] * 2", "-4"),
+    ]);
+    let (success, output) =
+        run("fn f(x) { return x + 1; } let v = [5]; print(\"{-f(2)} {-f(1) * 2} {-v[0]}\");");
+    assert!(success);
+    assert_eq!(output, "-3 -4 -5");
+    common::rejected(
+        "print(\"{-true}\");",
+        "can only use minus on ints and floats",
+    );
+    common::rejected("print(\"{!1}\");", "can only use ! on bools");
+}
+
+#[test]
+fn increments() {
+    //x++ and x-- are statements, -- anywhere a value goes is still two minuses
+    let (success, output) =
+        run("let x = 5; x++; x++; x--; let v = [1, 2]; v[-1]++; v[0]--; print(\"{x} {v} {--x}\");");
+    assert!(success);
+    assert_eq!(output, "6 [0, 3] 6");
+    for source in ["let y = 1; let z = y++;", "let y = 1; ++y;", "5++;"] {
+        assert!(!run(source).0, "{source} should not run");
+    }
+}
@@ -0,0 +1,46 @@
+//ranges: any expression for the start, end and step, checked when they run
+mod common;
+
+use common::{check, rejected};
+
+#[test]
+fn expressions() {
+    check(
+        r#"
+let v = [1, 2, 3, 4];
+let n = 5;
+print("{0..n-1} {0..std::len(v)} {1..10..n-2} {n*2..n*2+2} {(1+1)..4}");
+print("{5..0} {5..0..2} {-2..2}");
+fn f() { return 3; }
+print("{0..f()} {v[1..std::len(v)-1]}");
+for i in 0..n - 3 { print("{i}"); }
+"#,
+        &[
+            "[0, 1, 2, 3] [0, 1, 2, 3] [1, 4, 7] [10, 11] [2, 3]",
+            "[] [] [-2, -1, 0, 1]",
+            "[0, 1, 2] [2, 3]",
+            "0",
+            "1",
+        ],
+    );
+}
+
+#[test]
+fn errors() {
+    rejected(
+        "print(\"{0..1.5}\");",
+        "range end must be a whole number but its 1.5",
+    );
+    rejected(
+        "print(\"{0..5..0}\");",
+        "range step must be at least 1 but its 0",
+    );
+    rejected(
+        "for i in 10..0..-3 { }",
+        "range step must be at least 1 but its -3",
+    );
+    rejected(
+        "let s = \"a\";\nprint(\"{0..s}\");",
+        "range end must be a number but its a",
+    );
+}
@@ -0,0 +1,61 @@
+//a file with many mistakes gets all of them in one run, and nothing of it runs
+mod common;
+
+use common::run;
+
+//the first line of every error with where it is, like "[ParserError]: ... at 2:12"
+fn errors(source: &str) -> Vec<String> {
+    let output = run(source);
+    assert!(!output.success, "should not run:\n{source}");
+    assert!(output.stdout.is_empty(), "printed:\n{}", output.stdout);
+    let lines: Vec<&str> = output.stderr.lines().collect();
+    lines
+        .iter()
+        .enumerate()
+        .filter(|(_, line)| line.starts_with('['))
+        .map(|(i, line)| {
+            let position = lines[i + 1].rsplit(".klang:").next().unwrap();
+            format!("{line} at {position}")
+        })
+        .collect()
+}
+
+#[test]
+fn parser() {
+    assert_eq!(
+        errors("let a = ;\nlet b = 1 +;\nfn f( { }\nlet ok = 1;\nwhile { }\nlet c = 2\n"),
+        [
+            "[ParserError]: expected value found Semicolon at 1:9",
+            "[ParserError]: expected value found Semicolon at 2:12",
+            "[ParserError]: argument must be an identifier at 3:7",
+            "[ParserError]: expected a value before the { at 5:7",
+            "[ParserError]: missing ; at the end of the line at 6:10",
+        ]
+    );
+    //the good lines between the errors dont run
+    assert_eq!(
+        errors("let a = 1;\nprint(\"{a}\");\nlet b = 1 +;\n"),
+        ["[ParserError]: expected value found Semicolon at 3:12"]
+    );
+}
+
+#[test]
+fn scanner() {
+    //bad characters become error tokens, the scanner goes on and the parser still runs after it
+    assert_eq!(
+        errors("let a = 0b12 + $;\nlet b = @;\nlet c = 1 +;\n"),
+        [
+            "[ScannerError]: 2 is not a valid binary digit at 1:9",
+            "[ScannerError]: unexpected character at 1:16",
+            "[ScannerError]: unexpected character at 2:9",
+            "[ParserError]: expected value found Semicolon at 3:12",
+        ]
+    );
+    assert_eq!(
+        errors("let a = 1__0;\nlet s = \"open\n"),
+        [
+            "[ScannerError]: _ can only go between digits at 1:9",
+            "[ScannerError]: unterminated string at 2:9",
+        ]
+    );
+}
@@ -0,0 +1,60 @@
+//every error points at the exact part of the code it is about, not just the line
+mod common;
+
+use common::run_with;
+
+//the span of the first error as line:column-end_line:end_column and the bytes it covers
+fn span(source: &str) -> String {
+    let output = run_with(source, &["--error-format=json"]);
+    assert!(!output.success, "should not run:\n{source}");
+    let line = output.stderr.lines().next().unwrap();
+    let span = &line[line.find("\"span\":{").unwrap() + 8..];
+    let span = &span[..span.find('}').unwrap()];
+    let field = |name: &str| -> usize {
+        let value = &span[span.find(&format!("\"{name}\":")).unwrap() + name.len() + 3..];
+        value.split(',').next().unwrap().parse().unwrap()
+    };
+    format!(
+        "{}:{}-{}:{} {}..{}",
+        field("line"),
+        field("column"),
+        field("end_line"),
+        field("end_column"),
+        field("start"),
+        field("end")
+    )
+}
+
+#[test]
+fn sub_expressions() {
+    //the inner (2 * [1]) not the whole let
+    assert_eq!(span("let x = 1 + (2 * [1]);"), "1:14-1:21 13..20");
+    assert_eq!(
+        span("let v = [1, 2];\nlet w = v[0] + v[9];"),
+        "2:16-2:20 31..35"
+    );
+    assert_eq!(span("let q = std::sqrt(true);"), "1:9-1:24 8..23");
+    //inside the function, not where it was called
+    assert_eq!(
+        span("fn f(a) { return a / \"s\"; }\nlet z = f(2);"),
+        "1:18-1:25 17..24"
+    );
+}
+
+#[test]
+fn multi_byte() {
+    //columns count characters, start and end count bytes
+    assert_eq!(
+        span("let é = \"ü\"; let y = é + 1 - true;"),
+        "1:22-1:27 23..29"
+    );
+    let output = run_with("let é = \"ü\"; let y = é + 1 - true;", &[]);
+    assert!(output
+        .stderr
+        .contains("1 | let é = \"ü\"; let y = é + 1 - true;\n  |                      ^^^^^"));
+}
+
+#[test]
+fn multi_line() {
+    assert_eq!(span("let z = 1 +\n    \"a\";"), "1:9-2:8 8..19");
+}
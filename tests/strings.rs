@@ -0,0 +1,115 @@
+//string literals: escapes, braces, formatting and the other kinds of strings
+mod common;
+
+use common::{check, rejected};
+
+#[test]
+fn escapes() {
+    check(
+        r#"
+print("tab\there \"q\" back\\slash");
+print("\u{1F600} \u{e9}");
+print("one\ntwo");
+print("use \{x\} to print x");
+"#,
+        &[
+            "tab\there \"q\" back\\slash",
+            "😀 é",
+            "one",
+            "two",
+            "use {x} to print x",
+        ],
+    );
+    rejected(r#"print("\q");"#, "unknown escape \\q");
+    rejected(r#"print("\u{zz}");"#, "unterminated unicode escape");
+    rejected(
+        r#"print("\u{110000}");"#,
+        "110000 is not a valid unicode character",
+    );
+    rejected(r#"print("{}");"#, "cannot print an empty identifier");
+}
+
+#[test]
+fn formatting() {
+    check(
+        r#"
+let x = 5;
+print("{x} + 1 = {x + 1}, pi is about {std::round(std::pi())}");
+print("{[1, 2]} {x > 3} {x * 2}{x}");
+"#,
+        &["5 + 1 = 6, pi is about 3", "[1, 2] true 105"],
+    );
+}
+
+#[test]
+fn formatted_values() {
+    //a formatted string is filled in where it is, it can be stored and passed like any value
+    check(
+        r#"
+let x = 5;
+let s = "v={x} \{lit\}";
+print("{s}");
+let t = "v=5 \{lit\}";
+print("{s == t} {std::len(s)}");
+let v = ["a{x}", "{x + 1}{x}", 3];
+print("{v}");
+fn greet(name, end) {
+    return "hi {name}{end}";
+}
+let bob = "bob";
+let g = greet("x={x}", 1);
+print("{greet(bob, x)} {g}");
+let m = {"k{x}": 1};
+print("{m}");
+x = 6;
+print("{s}");
+"#,
+        &[
+            "v=5 {lit}",
+            "true 9",
+            "[a5, 65, 3]",
+            "hi bob5 hi x=51",
+            "{k5: 1}",
+            "v=5 {lit}",
+        ],
+    );
+}
+
+#[test]
+fn raw() {
+    //no escapes and no formatting, the text is taken as written
+    check(
+        r###"
+let a = r"C:\path\{x}";
+let b = r#"say "hi" {x}"#;
+let c = r##"a "# b"##;
+let g = "C:\\path\\\{x\}";
+print("{a}|{b}|{c}");
+print("{std::len(a)} {a == g}");
+"###,
+        &[r##"C:\path\{x}|say "hi" {x}|a "# b"##, "11 true"],
+    );
+    rejected("let a = r#\"abc\";", "unterminated raw string");
+}
+
+#[test]
+fn triple_quoted() {
+    //the indentation all the lines share is taken off, and so are the first and last newline
+    check(
+        r#"
+let d = """
+    select *
+      from t
+    where x = {x}
+    """;
+let e = """one line""";
+print("{d}");
+print("{e}");
+"#,
+        &["select *", "  from t", "where x = {x}", "one line"],
+    );
+    rejected(
+        "let a = \"\"\"abc",
+        "multi-line strings must be closed with \"\"\"",
+    );
+}
@@ -0,0 +1,116 @@
+//structs: making them, reading and changing fields, and the functions from their impl
+mod common;
+
+use common::{check, rejected};
+
+#[test]
+fn fields() {
+    check(
+        r#"
+struct Point { x, y }
+let p = Point { x: 1, y: 2 };
+print("{p} {p.x}");
+p.x = 5;
+p.y += 1;
+print("{p}");
+let q = Point { y: 0, x: [1] };
+q.x[0] = 9;
+print("{q} {q.x}");
+"#,
+        &[
+            "Point { x: 1, y: 2 } 1",
+            "Point { x: 5, y: 3 }",
+            "Point { x: [9], y: 0 } [9]",
+        ],
+    );
+}
+
+#[test]
+fn values() {
+    //a struct is copied like a vector, changing the copy leaves the first one alone
+    check(
+        r#"
+struct Point { x, y }
+struct Line { a, b }
+let p = Point { x: 1, y: 2 };
+let l = Line { a: p, b: p };
+l.a.x = 100;
+print("{l.a.x} {l.b.x} {p.x}");
+"#,
+        &["100 1 1"],
+    );
+}
+
+#[test]
+fn errors() {
+    rejected(
+        "struct P { x }\nlet p = P { x: 1 };\nprint(\"{p.z}\");",
+        "P has no field called z",
+    );
+    rejected(
+        "struct P { x }\nlet p = P { x: 1 };\np.z = 1;",
+        "P has no field called z",
+    );
+    rejected("struct P { x }\nlet p = P { };", "P is missing the field x");
+    rejected(
+        "struct P { x }\nlet p = P { x: 1, y: 2 };",
+        "P has no field called y",
+    );
+    rejected("struct P { x, x }", "P already has a field called x");
+    rejected("let p = 5;\nprint(\"{p.x}\");", "cant get the field x of 5");
+}
+
+#[test]
+fn methods() {
+    check(
+        r#"
+struct Point { x, y }
+impl Point {
+    fn new(x, y) { return Point { x: x, y: y }; }
+    fn len2(self) { return self.x * self.x + self.y * self.y; }
+    fn moved(self, dx, dy) { self.x += dx; self.y += dy; return self; }
+    fn origin() { return Point::new(0, 0); }
+}
+let p = Point::new(3, 4);
+print("{p.len2()} {Point::origin()}");
+let q = p.moved(1, 1);
+print("{p} {q}");
+p = p.moved(1, 1).moved(1, 1);
+print("{p} {Point::len2(p)}");
+"#,
+        &[
+            "25 Point { x: 0, y: 0 }",
+            "Point { x: 3, y: 4 } Point { x: 4, y: 5 }",
+            "Point { x: 5, y: 6 } 61",
+        ],
+    );
+    //without a method of that name a field holding a function is called
+    check(
+        r#"
+struct Box { f }
+let b = Box { f: fn(n) { return n * 2; } };
+print("{b.f(4)}");
+"#,
+        &["8"],
+    );
+}
+
+#[test]
+fn method_errors() {
+    rejected(
+        "struct P { x }\nimpl P { fn a(self) { return 1; } }\nlet p = P { x: 1 };\nprint(\"{p.b()}\");",
+        "P has no function called b",
+    );
+    rejected(
+        "struct P { x }\nimpl P { fn a(self) { return 1; } }\nprint(\"{P::b()}\");",
+        "P has no function called b",
+    );
+    rejected(
+        "struct P { x }\nimpl P { let a = 1; }",
+        "only functions can go in an impl",
+    );
+    rejected(
+        "let v = [1];\nprint(\"{v.len()}\");",
+        "cant call the method len on [1]",
+    );
+}